@@ -0,0 +1,15 @@
+//! Embeds the build time, the earliest time sybau will believe a source or its
+//! own clock. `SOURCE_DATE_EPOCH` overrides it for reproducible builds.
+
+use std::env;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    println!("cargo:rerun-if-changed=src");
+    let secs = env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+        .unwrap_or_else(|| SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()));
+    println!("cargo:rustc-env=SYBAU_BUILD_TIME={secs}");
+}
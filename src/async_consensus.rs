@@ -0,0 +1,85 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::task::JoinSet;
+use tokio::time::{timeout_at, Instant};
+
+use crate::clock::ClockHandle;
+use crate::config::Config;
+use crate::consensus::{log_outcome, Consensus};
+use crate::policy::ConsensusPolicy;
+use crate::result::ConsensusTime;
+use crate::source::{AsyncTimeSource, SourceError, TimeSample};
+
+// -- AsyncTimeConsensus --
+
+/// The async counterpart of [`TimeConsensus`](crate::TimeConsensus): the same
+/// agreement rules, with sources queried as tasks on the current tokio runtime.
+pub type AsyncTimeConsensus = Consensus<dyn AsyncTimeSource>;
+
+impl Default for AsyncTimeConsensus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AsyncTimeConsensus {
+    /// Uses the default config and a freshly started fallback clock.
+    pub fn new() -> Self {
+        Self::from_config(&Config::default())
+    }
+
+    /// Uses the enabled sources, the policy and the state file of `config`.
+    pub fn from_config(config: &Config) -> Self {
+        Self::with_sources(config.enabled_sources().iter().map(|s| s.build_async()).collect()).configured(config)
+    }
+
+    pub fn with_sources(sources: Vec<Box<dyn AsyncTimeSource>>) -> Self {
+        Self::with_clock(sources, ClockHandle::start())
+    }
+
+    /// Agrees on a time from the sources under the configured policy,
+    /// disciplining the fallback clock on success.
+    pub async fn get_date_time(&self) -> ConsensusTime {
+        self.get_date_time_with(&self.core.policy).await
+    }
+
+    /// Like [`AsyncTimeConsensus::get_date_time`], but under `policy`.
+    pub async fn get_date_time_with(&self, policy: &ConsensusPolicy) -> ConsensusTime {
+        let outcomes = self.fetch_all(policy.deadline()).await;
+        let sources: Vec<(&str, f64)> = self.sources.iter().map(|s| (s.name(), s.weight())).collect();
+        let result = self.core.settle(policy, &sources, outcomes);
+        if !result.used_fallback {
+            // Writing the state file blocks on fsync; keep it off the runtime's workers.
+            let (floor, time) = (Arc::clone(&self.core.floor), result.time);
+            let _ = tokio::task::spawn_blocking(move || floor.raise(time)).await;
+        }
+        result
+    }
+
+    /// Queries every source as its own task and collects what arrives before
+    /// `deadline`, one outcome per source. Stragglers are aborted.
+    async fn fetch_all(&self, deadline: Duration) -> Vec<Result<TimeSample, SourceError>> {
+        let mut tasks = JoinSet::new();
+        for (k, source) in self.sources.iter().enumerate() {
+            let source = Arc::clone(source);
+            tasks.spawn(async move {
+                tracing::trace!(source = source.name(), "querying source");
+                let started = Instant::now();
+                let outcome = source.fetch().await;
+                log_outcome(source.name(), started.elapsed(), &outcome);
+                (k, outcome)
+            });
+        }
+
+        let until = Instant::now() + deadline;
+        let mut outcomes = vec![Err(SourceError::MissedDeadline); self.sources.len()];
+        // Stops at the deadline, or early once every task has finished.
+        while let Ok(Some(joined)) = timeout_at(until, tasks.join_next()).await {
+            if let Ok((k, outcome)) = joined {
+                outcomes[k] = outcome;
+            }
+        }
+        outcomes
+    }
+}
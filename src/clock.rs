@@ -0,0 +1,256 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use chrono::{DateTime, Datelike, NaiveDate, NaiveTime, TimeZone, Timelike, Utc};
+
+use crate::leap::LeapSecondTable;
+
+// -- SoftClock (Fallback Time) --
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SoftClock {
+    pub sec: u8,
+    pub min: u8,
+    pub hour: u8,
+    pub day: u8,
+    pub month: u8,
+    pub year: u16,
+}
+
+impl SoftClock {
+    pub fn from_system_time() -> Self {
+        Self::from_utc(Utc::now())
+    }
+
+    pub fn from_utc(time: DateTime<Utc>) -> Self {
+        Self {
+            // chrono encodes a leap second as 23:59:59 with nanoseconds past 1e9.
+            sec: (time.second() + time.nanosecond() / 1_000_000_000) as u8,
+            min: time.minute() as u8,
+            hour: time.hour() as u8,
+            day: time.day() as u8,
+            month: time.month() as u8,
+            year: time.year() as u16,
+        }
+    }
+
+    /// The clock reading as a UTC instant, or `None` if the fields don't form a valid date.
+    pub fn to_utc(&self) -> Option<DateTime<Utc>> {
+        let date = NaiveDate::from_ymd_opt(self.year as i32, self.month as u32, self.day as u32)?;
+        let time = if self.sec == 60 {
+            NaiveTime::from_hms_milli_opt(self.hour as u32, self.min as u32, 59, 1_000)?
+        } else {
+            NaiveTime::from_hms_opt(self.hour as u32, self.min as u32, self.sec as u32)?
+        };
+        Some(Utc.from_utc_datetime(&date.and_time(time)))
+    }
+
+    /// Advances the clock by one second, ignoring leap seconds.
+    pub fn tick(&mut self) {
+        self.tick_with(&LeapSecondTable::default());
+    }
+
+    /// Advances the clock by one second, inserting `23:59:60` on the days listed in `leap_seconds`.
+    pub fn tick_with(&mut self, leap_seconds: &LeapSecondTable) {
+        let last_second = if self.hour == 23 && self.min == 59 && self.is_leap_day(leap_seconds) {
+            60
+        } else {
+            59
+        };
+
+        self.sec += 1;
+        if self.sec > last_second {
+            self.sec = 0;
+            self.min += 1;
+            if self.min >= 60 {
+                self.min = 0;
+                self.hour += 1;
+                if self.hour >= 24 {
+                    self.hour = 0;
+                    self.day += 1;
+                    if self.day > days_in_month(self.year, self.month) {
+                        self.day = 1;
+                        self.month += 1;
+                        if self.month > 12 {
+                            self.month = 1;
+                            self.year += 1;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn is_leap_day(&self, leap_seconds: &LeapSecondTable) -> bool {
+        NaiveDate::from_ymd_opt(self.year as i32, self.month as u32, self.day as u32)
+            .is_some_and(|date| leap_seconds.has_leap_second(date))
+    }
+}
+
+fn is_leap_year(year: u16) -> bool {
+    (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400)
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        _ => 31,
+    }
+}
+
+/// A host-clock reading paired with the monotonic instant it was taken at.
+#[derive(Debug, Clone, Copy)]
+struct Anchor {
+    instant: Instant,
+    utc: DateTime<Utc>,
+    /// Correction still being slewed in, in nanoseconds.
+    slew_nanos: i64,
+}
+
+/// How [`ClockHandle::discipline`] corrects the clock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Discipline {
+    /// Offsets at least this large are stepped; smaller ones are slewed.
+    pub step_threshold: Duration,
+    /// Maximum slew rate, in parts per million of elapsed time.
+    pub max_slew_ppm: u32,
+}
+
+impl Default for Discipline {
+    /// ntpd's defaults: step at 128 ms, slew at up to 500 ppm.
+    fn default() -> Self {
+        Self {
+            step_threshold: Duration::from_millis(128),
+            max_slew_ppm: 500,
+        }
+    }
+}
+
+/// What [`ClockHandle::discipline`] did with an offset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Adjustment {
+    /// The clock jumped straight to the trusted time.
+    Step(chrono::Duration),
+    /// The offset will be worked off gradually.
+    Slew(chrono::Duration),
+}
+
+/// The fallback clock: an anchor plus the monotonic time elapsed since it.
+///
+/// The current time is computed on demand, so there is no background thread
+/// and no drift from sleep overshoot or lock contention.
+pub struct ClockHandle {
+    anchor: Mutex<Anchor>,
+    leap_seconds: LeapSecondTable,
+    discipline: Discipline,
+}
+
+impl ClockHandle {
+    /// Anchors the clock to the host time now.
+    pub fn start() -> Self {
+        Self::start_with_leap_seconds(LeapSecondTable::default())
+    }
+
+    /// Anchors the clock to the host time now, honouring the given leap-second table.
+    pub fn start_with_leap_seconds(leap_seconds: LeapSecondTable) -> Self {
+        Self::anchored(Instant::now(), Utc::now(), leap_seconds)
+    }
+
+    /// A clock that read `utc` at `instant`.
+    pub fn anchored(instant: Instant, utc: DateTime<Utc>, leap_seconds: LeapSecondTable) -> Self {
+        Self {
+            anchor: Mutex::new(Anchor { instant, utc, slew_nanos: 0 }),
+            leap_seconds,
+            discipline: Discipline::default(),
+        }
+    }
+
+    pub fn with_discipline(mut self, discipline: Discipline) -> Self {
+        self.discipline = discipline;
+        self
+    }
+
+    pub fn leap_seconds(&self) -> &LeapSecondTable {
+        &self.leap_seconds
+    }
+
+    /// The current UTC time according to the clock.
+    pub fn now(&self) -> DateTime<Utc> {
+        self.time_at(Instant::now())
+    }
+
+    /// The UTC time the clock shows at `instant`. Instants before the anchor read as the anchor.
+    pub fn time_at(&self, instant: Instant) -> DateTime<Utc> {
+        let anchor = *self.anchor.lock().unwrap();
+        self.read(&anchor, instant)
+    }
+
+    /// Corrects the clock towards a trusted reading taken now.
+    pub fn discipline(&self, trusted: DateTime<Utc>) -> Adjustment {
+        self.discipline_at(Instant::now(), trusted)
+    }
+
+    /// Corrects the clock towards `trusted`, the true time at `instant`.
+    ///
+    /// Offsets of at least [`Discipline::step_threshold`] re-anchor the clock
+    /// outright; smaller ones are slewed in at [`Discipline::max_slew_ppm`].
+    pub fn discipline_at(&self, instant: Instant, trusted: DateTime<Utc>) -> Adjustment {
+        let mut anchor = self.anchor.lock().unwrap();
+        let current = self.read(&anchor, instant);
+        let offset = trusted - current;
+
+        let step = offset.abs().to_std().unwrap_or(Duration::MAX) >= self.discipline.step_threshold;
+        *anchor = if step {
+            Anchor { instant, utc: trusted, slew_nanos: 0 }
+        } else {
+            Anchor { instant, utc: current, slew_nanos: offset.num_nanoseconds().unwrap_or_default() }
+        };
+
+        if step {
+            Adjustment::Step(offset)
+        } else {
+            Adjustment::Slew(offset)
+        }
+    }
+
+    fn read(&self, anchor: &Anchor, instant: Instant) -> DateTime<Utc> {
+        let elapsed = instant.saturating_duration_since(anchor.instant);
+        let max_slew = (elapsed.as_nanos() * self.discipline.max_slew_ppm as u128 / 1_000_000)
+            .min(i64::MAX as u128) as i64;
+        let slewed = anchor.slew_nanos.clamp(-max_slew, max_slew);
+        advance(anchor.utc, elapsed, &self.leap_seconds) + chrono::Duration::nanoseconds(slewed)
+    }
+
+    pub fn get_time(&self) -> SoftClock {
+        SoftClock::from_utc(self.now())
+    }
+}
+
+/// Adds `elapsed` SI seconds to `start`, letting inserted leap seconds hold the label back.
+fn advance(start: DateTime<Utc>, elapsed: Duration, leap_seconds: &LeapSecondTable) -> DateTime<Utc> {
+    let starts_on_leap = start.nanosecond() >= 1_000_000_000;
+    let mut secs = start.timestamp() + starts_on_leap as i64 + elapsed.as_secs() as i64;
+    let mut nanos = start.nanosecond() % 1_000_000_000 + elapsed.subsec_nanos();
+    if nanos >= 1_000_000_000 {
+        secs += 1;
+        nanos -= 1_000_000_000;
+    }
+
+    for day in leap_seconds.days() {
+        // The leap second sits just before this midnight.
+        let midnight = day.succ_opt().and_then(|d| d.and_hms_opt(0, 0, 0)).unwrap().and_utc();
+        let leap = midnight.timestamp();
+        if leap <= start.timestamp() || (starts_on_leap && leap == start.timestamp() + 1) {
+            continue;
+        }
+        if secs == leap {
+            return DateTime::from_timestamp(leap - 1, nanos + 1_000_000_000).unwrap();
+        }
+        if secs > leap {
+            secs -= 1;
+        }
+    }
+
+    DateTime::from_timestamp(secs, nanos).unwrap()
+}
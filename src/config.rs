@@ -0,0 +1,280 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::policy::ConsensusPolicy;
+use crate::service::ResyncPolicy;
+use crate::source::DEFAULT_SOURCES;
+
+/// How a source's response body is interpreted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ParserKind {
+    /// `{"datetime": "<RFC 3339>"}`, as served by worldtimeapi.org.
+    #[default]
+    WorldTimeApi,
+    /// `{"dateTime": "<local time>", "timeZone": "<IANA zone>"}`, as served by timeapi.io.
+    TimeApiIo,
+    /// `{"currentDateTime": "<YYYY-MM-DDTHH:MMZ>"}`, as served by worldclockapi.com.
+    WorldClockApi,
+    /// Any JSON body: the value at `pointer`, parsed with `format`.
+    JsonPointer,
+    /// Not HTTP: an SNTPv4 server at `url` (`host[:port]` or `sntp://host[:port]`).
+    Sntp,
+    /// Any HTTP(S) server: the RFC 7231 `Date` response header, to the second.
+    HttpDate,
+}
+
+/// One time source as written in a config file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SourceConfig {
+    pub url: String,
+    #[serde(default)]
+    pub parser: ParserKind,
+    /// Relative influence on the averaged time.
+    #[serde(default = "default_weight")]
+    pub weight: f64,
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// RFC 6901 pointer to the timestamp, for [`ParserKind::JsonPointer`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pointer: Option<String>,
+    /// strftime format of the timestamp, for [`ParserKind::JsonPointer`]; RFC 3339 if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+fn default_timeout_ms() -> u64 {
+    5_000
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl SourceConfig {
+    /// An enabled source with the default parser, weight and timeout.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            parser: ParserKind::default(),
+            weight: default_weight(),
+            timeout_ms: default_timeout_ms(),
+            enabled: default_enabled(),
+            pointer: None,
+            format: None,
+        }
+    }
+
+    pub fn with_parser(mut self, parser: ParserKind) -> Self {
+        self.parser = parser;
+        self
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_millis(self.timeout_ms)
+    }
+}
+
+/// The set of sources to query, loaded from TOML or JSON.
+///
+/// ```toml
+/// [[sources]]
+/// url = "https://time.internal.example/api/now"
+/// parser = "world-time-api"
+/// weight = 2.0
+/// timeout_ms = 1500
+///
+/// [[sources]]
+/// url = "https://status.internal.example/health"
+/// parser = "json-pointer"
+/// pointer = "/clock/epoch"
+/// format = "%s"
+///
+/// [[sources]]
+/// url = "sntp://ntp.internal.example"
+/// parser = "sntp"
+///
+/// [[sources]]
+/// url = "https://www.example.com/"
+/// parser = "http-date"
+///
+/// [policy]
+/// min_quorum = 3
+///
+/// [resync]
+/// interval_secs = 600
+/// ```
+///
+/// A top-level `state_file = "/var/lib/sybau/clock"` keeps the last agreed
+/// time across restarts; see [`StateFile`](crate::StateFile).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Config {
+    pub sources: Vec<SourceConfig>,
+    #[serde(default)]
+    pub policy: ConsensusPolicy,
+    /// Only used by [`TimeService`](crate::TimeService).
+    #[serde(default)]
+    pub resync: ResyncPolicy,
+    /// Where to persist the last agreed time, if anywhere.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_file: Option<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            sources: DEFAULT_SOURCES
+                .iter()
+                .map(|&(url, parser)| SourceConfig::new(url).with_parser(parser))
+                .collect(),
+            policy: ConsensusPolicy::default(),
+            resync: ResyncPolicy::default(),
+            state_file: None,
+        }
+    }
+}
+
+impl Config {
+    /// Reads a config file, choosing JSON for `.json` files and TOML otherwise.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)?;
+        let config = if path.extension().is_some_and(|ext| ext == "json") {
+            Self::from_json(&text)?
+        } else {
+            Self::from_toml(&text)?
+        };
+        Ok(config)
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self, ConfigError> {
+        toml::from_str::<Self>(text)
+            .map_err(|e| ConfigError::Parse(e.to_string()))?
+            .validated()
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, ConfigError> {
+        serde_json::from_str::<Self>(text)
+            .map_err(|e| ConfigError::Parse(e.to_string()))?
+            .validated()
+    }
+
+    /// The sources that are switched on.
+    pub fn enabled_sources(&self) -> Vec<SourceConfig> {
+        self.sources.iter().filter(|s| s.enabled).cloned().collect()
+    }
+
+    /// Checks a config built or changed in code the way files are checked on load.
+    pub fn validated(self) -> Result<Self, ConfigError> {
+        if self.sources.iter().all(|s| !s.enabled) {
+            return Err(ConfigError::NoSources);
+        }
+        // Trust, agreement and diagnostics all key sources by URL.
+        let mut urls = HashSet::new();
+        if let Some(source) = self.sources.iter().find(|s| !urls.insert(s.url.as_str())) {
+            return Err(ConfigError::DuplicateSource(source.url.clone()));
+        }
+        if let Some(source) = self.sources.iter().find(|s| !(s.weight.is_finite() && s.weight > 0.0)) {
+            return Err(ConfigError::InvalidWeight(source.url.clone()));
+        }
+        if let Some(source) = self
+            .sources
+            .iter()
+            .find(|s| s.parser == ParserKind::JsonPointer && s.pointer.is_none())
+        {
+            return Err(ConfigError::MissingPointer(source.url.clone()));
+        }
+        let penalty = self.policy.trust.penalty;
+        if !(0.0..=1.0).contains(&penalty) {
+            return Err(ConfigError::InvalidPenalty(penalty));
+        }
+        let enabled = self.sources.iter().filter(|s| s.enabled).count();
+        if let Some(quorum) = self.policy.min_quorum {
+            if quorum == 0 || quorum > enabled {
+                return Err(ConfigError::UnreachableQuorum { quorum, enabled });
+            }
+        }
+        if self.resync.interval_secs == 0 {
+            return Err(ConfigError::ZeroInterval);
+        }
+        if self.resync.retry_secs == 0 || self.resync.max_backoff_secs == 0 {
+            return Err(ConfigError::ZeroBackoff);
+        }
+        if self.policy.horizon_days == 0 {
+            return Err(ConfigError::ZeroHorizon);
+        }
+        Ok(self)
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    Parse(String),
+    /// Every source is disabled, or none were listed.
+    NoSources,
+    /// The URL is listed more than once.
+    DuplicateSource(String),
+    /// The named source's weight isn't a positive number.
+    InvalidWeight(String),
+    /// The named `json-pointer` source has no `pointer`.
+    MissingPointer(String),
+    /// `policy.trust.penalty` isn't between 0 and 1.
+    InvalidPenalty(f64),
+    /// `policy.min_quorum` is zero or more than the enabled sources.
+    UnreachableQuorum { quorum: usize, enabled: usize },
+    /// `resync.interval_secs` is zero.
+    ZeroInterval,
+    /// `resync.retry_secs` or `resync.max_backoff_secs` is zero, which would
+    /// retry failing sources in a tight loop.
+    ZeroBackoff,
+    /// `policy.horizon_days` is zero.
+    ZeroHorizon,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(e) => write!(f, "cannot read config: {e}"),
+            ConfigError::Parse(e) => write!(f, "invalid config: {e}"),
+            ConfigError::NoSources => f.write_str("config enables no sources"),
+            ConfigError::DuplicateSource(url) => write!(f, "source {url} is listed more than once"),
+            ConfigError::InvalidWeight(url) => write!(f, "source {url} must have a positive weight"),
+            ConfigError::MissingPointer(url) => write!(f, "json-pointer source {url} needs a pointer"),
+            ConfigError::InvalidPenalty(penalty) => write!(f, "trust penalty {penalty} must be between 0 and 1"),
+            ConfigError::UnreachableQuorum { quorum, enabled } => {
+                write!(f, "quorum of {quorum} is unreachable with {enabled} enabled sources")
+            }
+            ConfigError::ZeroInterval => f.write_str("resync interval must be at least a second"),
+            ConfigError::ZeroBackoff => f.write_str("resync retry and backoff must be at least a second"),
+            ConfigError::ZeroHorizon => f.write_str("horizon must be at least a day"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ConfigError {
+    fn from(e: io::Error) -> Self {
+        ConfigError::Io(e)
+    }
+}
@@ -0,0 +1,544 @@
+use std::sync::{mpsc, Arc, Mutex};
+use std::fmt;
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use tracing::{debug, info, trace, warn};
+
+use crate::clock::ClockHandle;
+use crate::config::Config;
+use crate::marzullo::{marzullo, TimeInterval};
+use crate::policy::{build_time, ConsensusPolicy, Selection};
+use crate::result::{AgreementPath, ConsensusTime, SourceReading};
+use crate::source::{SourceError, TimeSample, TimeSource};
+use crate::state::{Floor, StateFile};
+use crate::trust::{SourceTrust, TrustOutcome, TrustTracker};
+
+// -- Consensus --
+
+/// Queries the network sources and agrees on a time, falling back to the
+/// software clock when the sources can't be trusted.
+///
+/// `S` is the kind of source queried: see [`TimeConsensus`] and
+/// `AsyncTimeConsensus`. Everything but the querying is shared.
+pub struct Consensus<S: ?Sized> {
+    pub(crate) sources: Vec<Arc<S>>,
+    pub(crate) core: Core,
+}
+
+/// Queries blocking [`TimeSource`]s, each on its own thread.
+pub type TimeConsensus = Consensus<dyn TimeSource>;
+
+impl<S: ?Sized> Consensus<S> {
+    pub fn with_clock(sources: Vec<Box<S>>, fallback_clock: ClockHandle) -> Self {
+        Self {
+            sources: sources.into_iter().map(Arc::from).collect(),
+            core: Core::new(fallback_clock),
+        }
+    }
+
+    /// Applies the policy and the state file of `config`.
+    pub(crate) fn configured(self, config: &Config) -> Self {
+        let consensus = self.with_policy(config.policy);
+        match &config.state_file {
+            Some(path) => consensus.with_state_file(path),
+            None => consensus,
+        }
+    }
+
+    /// Replaces the policy. Trust scores are reset if the trust policy changes.
+    pub fn with_policy(mut self, policy: ConsensusPolicy) -> Self {
+        self.core.set_policy(policy);
+        self
+    }
+
+    /// Persists each agreed time to `path`, and never reports a time earlier
+    /// than the one stored there. See [`StateFile`].
+    pub fn with_state_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.core.set_state_file(StateFile::new(path));
+        self
+    }
+
+    /// Believes times from `not_before` on rather than from the [`build_time`],
+    /// e.g. to replay recordings older than the build.
+    pub fn with_not_before(mut self, not_before: DateTime<Utc>) -> Self {
+        self.core.not_before = not_before;
+        self
+    }
+
+    pub fn policy(&self) -> &ConsensusPolicy {
+        &self.core.policy
+    }
+
+    /// The last trusted time, below which sources are rejected, if known.
+    pub fn floor(&self) -> Option<DateTime<Utc>> {
+        self.core.floor()
+    }
+
+    pub fn sources(&self) -> &[Arc<S>] {
+        &self.sources
+    }
+
+    pub fn fallback_clock(&self) -> &ClockHandle {
+        &self.core.fallback_clock
+    }
+
+    /// Current trust scores and their recent history, for diagnostics.
+    pub fn trust_report(&self) -> Vec<SourceTrust> {
+        self.core.trust_report()
+    }
+}
+
+impl Default for TimeConsensus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TimeConsensus {
+    /// Uses the default config and a freshly started fallback clock.
+    pub fn new() -> Self {
+        Self::from_config(&Config::default())
+    }
+
+    /// Uses the enabled sources, the policy and the state file of `config`.
+    pub fn from_config(config: &Config) -> Self {
+        Self::with_sources(config.enabled_sources().iter().map(|s| s.build()).collect()).configured(config)
+    }
+
+    pub fn with_sources(sources: Vec<Box<dyn TimeSource>>) -> Self {
+        Self::with_clock(sources, ClockHandle::start())
+    }
+
+    /// Agrees on a time from the sources under the configured policy,
+    /// disciplining the fallback clock on success.
+    pub fn get_date_time(&self) -> ConsensusTime {
+        self.get_date_time_with(&self.core.policy)
+    }
+
+    /// Like [`TimeConsensus::get_date_time`], but under `policy`.
+    pub fn get_date_time_with(&self, policy: &ConsensusPolicy) -> ConsensusTime {
+        let outcomes = self.fetch_all(policy.deadline());
+        let sources: Vec<(&str, f64)> = self.sources.iter().map(|s| (s.name(), s.weight())).collect();
+        let result = self.core.settle(policy, &sources, outcomes);
+        if !result.used_fallback {
+            self.core.floor.raise(result.time);
+        }
+        result
+    }
+
+    /// Queries every source on its own thread and collects what arrives before
+    /// `deadline`, one outcome per source. Stragglers finish in the background
+    /// and their answers are dropped.
+    fn fetch_all(&self, deadline: Duration) -> Vec<Result<TimeSample, SourceError>> {
+        let (tx, rx) = mpsc::channel();
+        for (k, source) in self.sources.iter().enumerate() {
+            let source = Arc::clone(source);
+            let tx = tx.clone();
+            thread::spawn(move || {
+                trace!(source = source.name(), "querying source");
+                let started = Instant::now();
+                let outcome = source.fetch();
+                log_outcome(source.name(), started.elapsed(), &outcome);
+                let _ = tx.send((k, outcome));
+            });
+        }
+        drop(tx);
+
+        let until = Instant::now() + deadline;
+        let mut outcomes = vec![Err(SourceError::MissedDeadline); self.sources.len()];
+        // Stops at the deadline, or early once every thread has reported.
+        while let Ok((k, outcome)) = rx.recv_timeout(until.saturating_duration_since(Instant::now())) {
+            outcomes[k] = outcome;
+        }
+        outcomes
+    }
+}
+
+// -- Agreement --
+
+/// Everything about a consensus run except how the sources are queried,
+/// shared by the blocking and async front ends.
+pub(crate) struct Core {
+    pub(crate) fallback_clock: ClockHandle,
+    pub(crate) policy: ConsensusPolicy,
+    /// The earliest believable time; the latest comes from the policy's horizon.
+    pub(crate) not_before: DateTime<Utc>,
+    trust: Mutex<TrustTracker>,
+    pub(crate) floor: Arc<Floor>,
+}
+
+impl Core {
+    pub(crate) fn new(fallback_clock: ClockHandle) -> Self {
+        let policy = ConsensusPolicy::default();
+        let trust = Mutex::new(TrustTracker::new(policy.trust));
+        Self {
+            fallback_clock,
+            policy,
+            not_before: build_time(),
+            trust,
+            floor: Arc::default(),
+        }
+    }
+
+    /// Loads the floor from `state_file`, stepping the fallback clock up to it
+    /// if the host clock started out earlier.
+    pub(crate) fn set_state_file(&mut self, state_file: StateFile) {
+        let loaded = match state_file.load() {
+            Ok(Some(floor)) => {
+                let clock = self.fallback_clock.now();
+                if clock < floor {
+                    warn!(
+                        %clock,
+                        %floor,
+                        path = %state_file.path().display(),
+                        "clock is behind the last trusted time, stepping forward"
+                    );
+                    self.fallback_clock.discipline(floor);
+                }
+                Some(floor)
+            }
+            Ok(None) => {
+                debug!(path = %state_file.path().display(), "no state file yet");
+                None
+            }
+            Err(error) => {
+                warn!(%error, path = %state_file.path().display(), "cannot read state file");
+                None
+            }
+        };
+        self.floor = Arc::new(Floor::new(Some(state_file), loaded));
+    }
+
+    pub(crate) fn floor(&self) -> Option<DateTime<Utc>> {
+        self.floor.get()
+    }
+
+    pub(crate) fn set_policy(&mut self, policy: ConsensusPolicy) {
+        self.policy = policy;
+        let trust = self.trust.get_mut().unwrap();
+        if trust.policy() != &policy.trust {
+            *trust = TrustTracker::new(policy.trust);
+        }
+    }
+
+    pub(crate) fn trust_report(&self) -> Vec<SourceTrust> {
+        self.trust.lock().unwrap().report_at(Instant::now())
+    }
+
+    /// Agrees on a time from `outcomes`, one per entry of `sources` (name,
+    /// weight), disciplining the fallback clock on success. The caller raises
+    /// the floor afterwards, since that may write to disk.
+    pub(crate) fn settle(
+        &self,
+        policy: &ConsensusPolicy,
+        sources: &[(&str, f64)],
+        outcomes: Vec<Result<TimeSample, SourceError>>,
+    ) -> ConsensusTime {
+        let floor = self.floor();
+        let (not_before, not_after) = (self.not_before, policy.not_after());
+        let mut samples = Vec::with_capacity(outcomes.len());
+        let mut errors = Vec::new();
+        let mut warnings = Vec::new();
+        for (k, outcome) in outcomes.into_iter().enumerate() {
+            let outcome = outcome.and_then(|sample| {
+                // Allow for the sample's own uncertainty before calling it out of bounds.
+                let (time, uncertainty) = (sample.time(), sample.uncertainty);
+                if time + uncertainty < not_before || time - uncertainty > not_after {
+                    return Err(SourceError::OutOfRange { time, not_before, not_after });
+                }
+                match floor {
+                    Some(floor) if time + uncertainty < floor => Err(SourceError::BeforeFloor { time, floor }),
+                    _ => Ok(sample),
+                }
+            });
+            match outcome {
+                Ok(sample) => {
+                    if let Some(mismatch) = sample.offset_mismatch {
+                        warnings.push((sources[k].0.to_string(), SourceError::OffsetMismatch(mismatch)));
+                    }
+                    samples.push((k, sample));
+                }
+                Err(error) => {
+                    match &error {
+                        SourceError::MissedDeadline => {
+                            warn!(source = sources[k].0, deadline_ms = policy.deadline_ms, "source missed the deadline")
+                        }
+                        SourceError::BeforeFloor { .. } | SourceError::OutOfRange { .. } => {
+                            warn!(source = sources[k].0, %error, "source rejected")
+                        }
+                        _ => {}
+                    }
+                    errors.push((sources[k].0.to_string(), error));
+                }
+            }
+        }
+
+        let mut result = match self.network_consensus(policy, sources, &samples) {
+            Ok(result) => {
+                info!(
+                    path = %result.path,
+                    time = %result.time,
+                    agreeing = result.sources.len(),
+                    queried = sources.len(),
+                    error_bound_ms = result.error_bound.map(|b| b.as_millis() as u64),
+                    "consensus reached"
+                );
+                self.fallback_clock.discipline(result.time);
+                result
+            }
+            Err(reason) => {
+                let result = ConsensusTime::fallback(self.bounded_clock(policy, floor));
+                warn!(%reason, time = %result.time, "no consensus, using the software clock");
+                result
+            }
+        };
+        result.readings = samples
+            .iter()
+            .map(|&(k, sample)| {
+                let name = sources[k].0;
+                SourceReading {
+                    name: name.to_string(),
+                    time: sample.time(),
+                    offset: sample.offset,
+                    uncertainty: sample.uncertainty,
+                    agreed: result.sources.iter().any(|agreed| agreed == name),
+                }
+            })
+            .collect();
+        result.errors = errors;
+        result.warnings = warnings;
+        result
+    }
+
+    /// The software clock's time, first stepped back within bounds if it has
+    /// strayed before the build or the floor, or past the horizon.
+    fn bounded_clock(&self, policy: &ConsensusPolicy, floor: Option<DateTime<Utc>>) -> DateTime<Utc> {
+        let clock = self.fallback_clock.now();
+        let earliest = floor.map_or(self.not_before, |floor| floor.max(self.not_before));
+        let bounded = clock.clamp(earliest, policy.not_after().max(earliest));
+        if bounded != clock {
+            warn!(%clock, time = %bounded, "software clock is out of range, stepping");
+            self.fallback_clock.discipline(bounded);
+        }
+        bounded
+    }
+
+    fn network_consensus(
+        &self,
+        policy: &ConsensusPolicy,
+        sources: &[(&str, f64)],
+        samples: &[(usize, TimeSample)],
+    ) -> Result<ConsensusTime, NoConsensus> {
+        if samples.is_empty() {
+            return Err(NoConsensus::NoReadings);
+        }
+
+        // Samples were taken at different moments; compare them all as of now.
+        let now = Utc::now();
+        let instant = Instant::now();
+        let mut trust = self.trust.lock().unwrap();
+        let readings: Vec<Reading> = samples
+            .iter()
+            .map(|&(k, sample)| {
+                let (name, weight) = sources[k];
+                Reading {
+                    name,
+                    time: now + sample.offset,
+                    uncertainty: sample.uncertainty,
+                    weight: weight * trust.score_at(name, instant),
+                }
+            })
+            .collect();
+
+        let intervals: Vec<TimeInterval> =
+            readings.iter().map(|r| r.interval(policy.tolerance())).collect();
+        let intersection = marzullo(&intervals).ok_or(NoConsensus::NoReadings)?;
+
+        // Sources that missed the deadline still count towards the quorum's
+        // denominator; only the degraded tiers look at responders alone.
+        let agreeing = intersection.members.len();
+        let required = policy.quorum(sources.len());
+        let path = if agreeing == sources.len() {
+            AgreementPath::Unanimous
+        } else if agreeing >= required {
+            AgreementPath::Quorum
+        } else if policy.min_quorum.is_some() {
+            return Err(NoConsensus::BelowQuorum { agreeing, required });
+        } else if readings.len() == 1 {
+            return self.cross_checked(policy, readings[0]);
+        } else if agreeing >= 2 && agreeing * 2 > readings.len() {
+            AgreementPath::Majority
+        } else {
+            return Err(NoConsensus::BelowQuorum { agreeing, required });
+        };
+
+        let agreeing: Vec<Reading> = intersection.members.iter().map(|&k| readings[k]).collect();
+        if let Some(max_spread) = policy.max_spread() {
+            let earliest = agreeing.iter().map(|r| r.time).min().unwrap_or(now);
+            let latest = agreeing.iter().map(|r| r.time).max().unwrap_or(now);
+            let spread = (latest - earliest).to_std().unwrap_or_default();
+            if spread > max_spread {
+                return Err(NoConsensus::TooSpread { spread, max_spread });
+            }
+        }
+
+        for (k, reading) in readings.iter().enumerate() {
+            let outcome = if intersection.members.contains(&k) {
+                TrustOutcome::Agreed
+            } else {
+                TrustOutcome::Disagreed
+            };
+            if outcome == TrustOutcome::Disagreed {
+                debug!(source = reading.name, time = %reading.time, "source disagreed");
+            }
+            trust.record_at(reading.name, outcome, instant, now);
+        }
+
+        Ok(agreed(path, policy.selection, &agreeing, intersection.interval))
+    }
+
+    /// Accepts the only reading that arrived if the software clock, disciplined
+    /// by earlier runs, lies inside its interval. Trust is left alone: there
+    /// was no other source to agree or disagree with.
+    fn cross_checked(&self, policy: &ConsensusPolicy, reading: Reading) -> Result<ConsensusTime, NoConsensus> {
+        let interval = reading.interval(policy.tolerance());
+        let clock = self.fallback_clock.now();
+        if !interval.contains(clock) {
+            return Err(NoConsensus::FailedCrossCheck { name: reading.name.to_string(), offset: reading.time - clock });
+        }
+        Ok(agreed(AgreementPath::SingleSource, policy.selection, &[reading], interval))
+    }
+}
+
+/// Logs one source's answer, or why there wasn't one.
+pub(crate) fn log_outcome(name: &str, latency: Duration, outcome: &Result<TimeSample, SourceError>) {
+    let latency_ms = latency.as_millis() as u64;
+    match outcome {
+        Ok(sample) => debug!(
+            source = name,
+            latency_ms,
+            offset_ms = sample.offset.num_milliseconds(),
+            uncertainty_ms = sample.uncertainty.as_millis() as u64,
+            time = %sample.time(),
+            "source answered"
+        ),
+        Err(error) => warn!(source = name, latency_ms, %error, "source failed"),
+    }
+}
+
+/// Why the network sources couldn't be trusted this time.
+#[derive(Debug)]
+enum NoConsensus {
+    NoReadings,
+    BelowQuorum { agreeing: usize, required: usize },
+    TooSpread { spread: Duration, max_spread: Duration },
+    /// The only responder is too far from the software clock.
+    FailedCrossCheck { name: String, offset: chrono::Duration },
+}
+
+impl fmt::Display for NoConsensus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NoConsensus::NoReadings => f.write_str("no source answered"),
+            NoConsensus::BelowQuorum { agreeing, required } => {
+                write!(f, "only {agreeing} sources agree, {required} required")
+            }
+            NoConsensus::TooSpread { spread, max_spread } => {
+                write!(f, "agreeing sources are {spread:?} apart, at most {max_spread:?} allowed")
+            }
+            NoConsensus::FailedCrossCheck { name, offset } => {
+                write!(f, "only {name} answered and it is {}ms from the software clock", offset.num_milliseconds())
+            }
+        }
+    }
+}
+
+/// A source's time projected onto a common local instant.
+#[derive(Clone, Copy)]
+struct Reading<'a> {
+    name: &'a str,
+    time: DateTime<Utc>,
+    uncertainty: Duration,
+    /// The source's configured weight scaled by its trust score.
+    weight: f64,
+}
+
+impl Reading<'_> {
+    /// Where the source says true time lies, widened by `tolerance`.
+    fn interval(&self, tolerance: Duration) -> TimeInterval {
+        let radius = chrono::Duration::from_std(self.uncertainty + tolerance).unwrap_or(chrono::Duration::MAX);
+        TimeInterval::around(self.time, radius)
+    }
+}
+
+/// Picks the agreed time from the agreeing readings, kept inside the agreed
+/// interval. The error bound is the distance to the interval's far edge.
+fn agreed(
+    path: AgreementPath,
+    selection: Selection,
+    readings: &[Reading],
+    interval: TimeInterval,
+) -> ConsensusTime {
+    let selected = match selection {
+        Selection::Mean => weighted_mean(&usable_weights(readings)),
+        Selection::Median => weighted_median(&usable_weights(readings)),
+    };
+    let time = selected.clamp(interval.earliest, interval.latest);
+    let error_bound = (time - interval.earliest).max(interval.latest - time);
+
+    ConsensusTime {
+        time,
+        sources: readings.iter().map(|r| r.name.to_string()).collect(),
+        path,
+        interval: Some(interval),
+        error_bound: error_bound.to_std().ok(),
+        used_fallback: false,
+        readings: Vec::new(),
+        errors: Vec::new(),
+        warnings: Vec::new(),
+    }
+}
+
+/// `readings` as they are, or equally weighted if their weights don't add up
+/// to a positive, finite total (e.g. a custom source weighted zero).
+fn usable_weights<'a>(readings: &[Reading<'a>]) -> Vec<Reading<'a>> {
+    let total: f64 = readings.iter().map(|r| r.weight).sum();
+    if total.is_finite() && total > 0.0 {
+        return readings.to_vec();
+    }
+    readings.iter().map(|&r| Reading { weight: 1.0, ..r }).collect()
+}
+
+fn weighted_mean(readings: &[Reading]) -> DateTime<Utc> {
+    let earliest = readings.iter().map(|r| r.time).min().unwrap();
+    let total_weight: f64 = readings.iter().map(|r| r.weight).sum();
+    let weighted_offset: f64 = readings
+        .iter()
+        .map(|r| r.weight * (r.time - earliest).num_microseconds().unwrap_or_default() as f64)
+        .sum();
+    earliest + chrono::Duration::microseconds((weighted_offset / total_weight) as i64)
+}
+
+/// The reading at which half the total weight lies on either side. When the
+/// split falls exactly between two readings, their midpoint.
+fn weighted_median(readings: &[Reading]) -> DateTime<Utc> {
+    let mut sorted = readings.to_vec();
+    sorted.sort_by_key(|r| r.time);
+    let half: f64 = sorted.iter().map(|r| r.weight).sum::<f64>() / 2.0;
+
+    let mut cumulative = 0.0;
+    for (k, reading) in sorted.iter().enumerate() {
+        cumulative += reading.weight;
+        if cumulative > half {
+            return reading.time;
+        }
+        if cumulative == half && k + 1 < sorted.len() {
+            let next = sorted[k + 1].time;
+            return reading.time + (next - reading.time) / 2;
+        }
+    }
+    sorted.last().unwrap().time
+}
@@ -0,0 +1,46 @@
+use chrono::NaiveDate;
+
+/// Days whose final minute carries an inserted leap second (`23:59:60`).
+///
+/// Only positive leap seconds are modelled; none has ever been removed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LeapSecondTable {
+    days: Vec<NaiveDate>,
+}
+
+/// Every leap second announced by the IERS up to the end of 2016.
+const IERS_LEAP_DAYS: [(i32, u32, u32); 27] = [
+    (1972, 6, 30), (1972, 12, 31), (1973, 12, 31), (1974, 12, 31), (1975, 12, 31),
+    (1976, 12, 31), (1977, 12, 31), (1978, 12, 31), (1979, 12, 31), (1981, 6, 30),
+    (1982, 6, 30), (1983, 6, 30), (1985, 6, 30), (1987, 12, 31), (1989, 12, 31),
+    (1990, 12, 31), (1992, 6, 30), (1993, 6, 30), (1994, 6, 30), (1995, 12, 31),
+    (1997, 6, 30), (1998, 12, 31), (2005, 12, 31), (2008, 12, 31), (2012, 6, 30),
+    (2015, 6, 30), (2016, 12, 31),
+];
+
+impl LeapSecondTable {
+    pub fn new(mut days: Vec<NaiveDate>) -> Self {
+        days.sort();
+        days.dedup();
+        Self { days }
+    }
+
+    /// The published IERS table.
+    pub fn iers() -> Self {
+        Self::new(
+            IERS_LEAP_DAYS
+                .iter()
+                .filter_map(|&(y, m, d)| NaiveDate::from_ymd_opt(y, m, d))
+                .collect(),
+        )
+    }
+
+    pub fn days(&self) -> &[NaiveDate] {
+        &self.days
+    }
+
+    /// Whether `date` ends with a 61-second minute.
+    pub fn has_leap_second(&self, date: NaiveDate) -> bool {
+        self.days.binary_search(&date).is_ok()
+    }
+}
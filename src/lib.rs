@@ -0,0 +1,32 @@
+#[cfg(feature = "async")]
+mod async_consensus;
+mod clock;
+mod config;
+mod consensus;
+mod leap;
+mod marzullo;
+mod policy;
+mod result;
+mod service;
+mod source;
+mod state;
+mod trust;
+
+#[cfg(feature = "async")]
+pub use async_consensus::AsyncTimeConsensus;
+pub use clock::{Adjustment, ClockHandle, Discipline, SoftClock};
+pub use config::{Config, ConfigError, ParserKind, SourceConfig};
+pub use consensus::{Consensus, TimeConsensus};
+pub use leap::LeapSecondTable;
+pub use marzullo::{marzullo, Intersection, TimeInterval};
+pub use policy::{build_time, ConsensusPolicy, Selection};
+pub use result::{system_zone, AgreementPath, ConsensusTime, SourceReading};
+pub use service::{ResyncPolicy, TimeEvent, TimeService};
+pub use source::{
+    fetch_time_from_url, from_ntp, parse_http_date, to_ntp, HttpDate, JsonPointer, OffsetMismatch, Sntp, SntpSample,
+    SourceError, TimeApiIo, TimeSample, TimeSource, WorldClockApi, WorldTimeApi, DEFAULT_SOURCES,
+};
+#[cfg(feature = "async")]
+pub use source::{AsyncTimeSource, Blocking, BoxFuture};
+pub use state::StateFile;
+pub use trust::{SourceTrust, TrustEvent, TrustOutcome, TrustPolicy, TrustTracker};
@@ -1,181 +1,294 @@
-use std::sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}};
-use std::thread;
-use std::time::Duration;
-use chrono::{Datelike, Timelike, Utc};
-use serde::Deserialize;
-
-// -- SoftClock Thread (Fallback Time) --
-
-#[derive(Debug, Clone, Copy)]
-struct SoftClock {
-    sec: u8,
-    min: u8,
-    hour: u8,
-    day: u8,
-    month: u8,
-    year: u16,
-}
-
-impl SoftClock {
-    fn from_system_time() -> Self {
-        let now = Utc::now();
-        Self {
-            sec: now.second() as u8,
-            min: now.minute() as u8,
-            hour: now.hour() as u8,
-            day: now.day() as u8,
-            month: now.month() as u8,
-            year: now.year() as u16,
-        }
-    }
+use std::path::PathBuf;
+use std::process::ExitCode;
 
-    fn tick(&mut self) {
-        self.sec += 1;
-        if self.sec >= 60 {
-            self.sec = 0;
-            self.min += 1;
-            if self.min >= 60 {
-                self.min = 0;
-                self.hour += 1;
-                if self.hour >= 24 {
-                    self.hour = 0;
-                    self.day += 1;
-                    if self.day > 31 {
-                        self.day = 1;
-                        self.month += 1;
-                        if self.month > 12 {
-                            self.month = 1;
-                            self.year += 1;
-                        }
-                    }
-                }
-            }
-        }
-    }
-}
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, SecondsFormat, TimeZone, Utc};
+use chrono_tz::Tz;
+use clap::Parser;
+use serde_json::{json, Value};
+use tracing_subscriber::EnvFilter;
+use sybau::{system_zone, Config, ConsensusTime, SourceConfig, SourceError, SourceReading, TimeConsensus, TimeEvent, TimeService};
 
-struct ClockHandle {
-    clock: Arc<Mutex<SoftClock>>,
-    ready: Arc<AtomicBool>,
-}
+/// Agree on the current UTC time from several network time sources.
+#[derive(Debug, Parser)]
+#[command(version)]
+struct Args {
+    /// TOML or JSON file listing the sources to query.
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+
+    /// Query this source instead of the configured ones (repeatable); the rest
+    /// of the config still applies. Options follow the URL, comma-separated:
+    /// parser=<kind>, weight=<w>, timeout_ms=<ms>, pointer=<ptr>, format=<strftime>.
+    /// Other commas stay in the URL or value unless followed by `word=`.
+    #[arg(long = "source", value_name = "URL[,KEY=VALUE...]", value_parser = parse_source)]
+    sources: Vec<SourceConfig>,
+
+    /// Keep the last agreed time in this file, and never report an earlier one.
+    #[arg(long, value_name = "PATH")]
+    state_file: Option<PathBuf>,
+
+    /// How to print the time: human, json, rfc3339, unix, or custom=<strftime>.
+    #[arg(long, value_name = "FORMAT", default_value = "human", value_parser = parse_format)]
+    format: Format,
+
+    /// Show the time in this IANA zone (e.g. America/New_York), or "system" for the local zone.
+    #[arg(long, value_name = "ZONE", default_value = "UTC", value_parser = parse_zone)]
+    tz: Tz,
 
-impl ClockHandle {
-    fn start() -> Self {
-        let clock = Arc::new(Mutex::new(SoftClock::from_system_time()));
-        let ready = Arc::new(AtomicBool::new(false));
+    /// Report how each source fared, and log at debug level unless RUST_LOG says otherwise.
+    #[arg(short, long)]
+    verbose: bool,
 
-        let clock_clone = Arc::clone(&clock);
-        let ready_clone = Arc::clone(&ready);
+    /// Keep running, resyncing periodically and reporting changes.
+    #[arg(long)]
+    watch: bool,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    init_logging(args.verbose);
 
-        thread::spawn(move || {
-            ready_clone.store(true, Ordering::SeqCst);
-            loop {
-                thread::sleep(Duration::from_secs(1));
-                let mut locked = clock_clone.lock().unwrap();
-                locked.tick();
+    let mut config = match &args.config {
+        Some(path) => match Config::from_path(path) {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("{}: {e}", path.display());
+                return ExitCode::FAILURE;
             }
-        });
+        },
+        None => Config::default(),
+    };
+    if !args.sources.is_empty() {
+        config.sources = args.sources;
+        config = match config.validated() {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("--source: {e}");
+                return ExitCode::FAILURE;
+            }
+        };
+    }
 
-        Self { clock, ready }
+    if args.state_file.is_some() {
+        config.state_file = args.state_file;
     }
 
-    fn is_ready(&self) -> bool {
-        self.ready.load(Ordering::SeqCst)
+    if args.watch {
+        watch(&config, args.tz);
+        return ExitCode::SUCCESS;
     }
 
-    fn get_time(&self) -> SoftClock {
-        self.clock.lock().unwrap().clone()
+    let consensus = TimeConsensus::from_config(&config);
+    let result = consensus.get_date_time();
+
+    let zoned = result.in_zone(args.tz);
+    match &args.format {
+        Format::Human if args.tz == Tz::UTC => println!("Final UTC Time: {}", zoned.format("%Y-%m-%d %H:%M:%S UTC")),
+        Format::Human => println!("Final Time: {} ({})", zoned.format("%Y-%m-%d %H:%M:%S %Z"), args.tz),
+        Format::Rfc3339 => println!("{}", rfc3339(zoned)),
+        Format::Unix => println!("{}", unix(result.time)),
+        Format::Custom(format) => println!("{}", zoned.format(format)),
+        Format::Json => println!("{:#}", to_json(&consensus, &result, args.tz)),
+    }
+    if args.verbose {
+        report(&consensus, &result);
+    }
+    ExitCode::SUCCESS
+}
+
+#[derive(Debug, Clone)]
+enum Format {
+    Human,
+    Json,
+    Rfc3339,
+    Unix,
+    Custom(String),
+}
+
+fn parse_format(value: &str) -> Result<Format, String> {
+    match value {
+        "human" => Ok(Format::Human),
+        "json" => Ok(Format::Json),
+        "rfc3339" => Ok(Format::Rfc3339),
+        "unix" => Ok(Format::Unix),
+        _ => {
+            let custom = value
+                .strip_prefix("custom=")
+                .ok_or("expected human, json, rfc3339, unix or custom=<strftime>")?;
+            if StrftimeItems::new(custom).any(|item| item == Item::Error) {
+                return Err(format!("invalid strftime format {custom:?}"));
+            }
+            Ok(Format::Custom(custom.to_string()))
+        }
     }
 }
 
-// -- Online Time Source Fetching --
+fn parse_source(value: &str) -> Result<SourceConfig, String> {
+    // A comma only starts an option when `key=` follows it, so query strings
+    // and strftime formats can keep theirs.
+    let mut fields: Vec<String> = Vec::new();
+    for part in value.split(',') {
+        match fields.last_mut() {
+            Some(field) if !starts_option(part) => {
+                field.push(',');
+                field.push_str(part);
+            }
+            _ => fields.push(part.to_string()),
+        }
+    }
 
-#[derive(Debug, Deserialize)]
-struct WorldTimeApiResponse {
-    datetime: String,
+    let mut fields = fields.iter();
+    let mut source = SourceConfig::new(fields.next().map(String::as_str).unwrap_or_default());
+    for field in fields {
+        let (key, value) = field.split_once('=').ok_or_else(|| format!("expected key=value, got {field:?}"))?;
+        match key {
+            "parser" => {
+                source.parser = serde_json::from_value(json!(value)).map_err(|_| format!("unknown parser {value:?}"))?
+            }
+            "weight" => source.weight = value.parse().map_err(|_| format!("invalid weight {value:?}"))?,
+            "timeout_ms" => source.timeout_ms = value.parse().map_err(|_| format!("invalid timeout {value:?}"))?,
+            "pointer" => source.pointer = Some(value.to_string()),
+            "format" => source.format = Some(value.to_string()),
+            _ => return Err(format!("unknown source option {key:?}")),
+        }
+    }
+    Ok(source)
 }
 
-fn fetch_time_from_url(url: &str) -> Option<(u8, u8, u16, u8, u8)> {
-    let response = reqwest::blocking::get(url).ok()?;
-    let json: WorldTimeApiResponse = response.json().ok()?;
+fn starts_option(part: &str) -> bool {
+    part.split_once('=')
+        .is_some_and(|(key, _)| !key.is_empty() && key.bytes().all(|b| b.is_ascii_lowercase() || b == b'_'))
+}
 
-    let datetime = json.datetime; // ISO 8601 format
-    let parsed = chrono::DateTime::parse_from_rfc3339(&datetime).ok()?;
-    let utc = parsed.with_timezone(&Utc);
+fn parse_zone(value: &str) -> Result<Tz, String> {
+    if value == "system" {
+        return system_zone().ok_or_else(|| "cannot determine the system time zone".to_string());
+    }
+    value.parse().map_err(|_| format!("unknown time zone {value:?}"))
+}
+
+fn rfc3339<Z: TimeZone>(time: DateTime<Z>) -> String
+where
+    Z::Offset: std::fmt::Display,
+{
+    time.to_rfc3339_opts(SecondsFormat::Micros, true)
+}
 
-    Some((
-        utc.day() as u8,
-        utc.month() as u8,
-        utc.year() as u16,
-        utc.hour() as u8,
-        utc.minute() as u8,
-    ))
+/// Seconds since the Unix epoch, to the millisecond.
+fn unix(time: DateTime<Utc>) -> String {
+    format!("{}.{:03}", time.timestamp(), time.timestamp_subsec_millis())
 }
 
-// -- Deviation Checking Utilities --
+fn millis(duration: chrono::Duration) -> Option<f64> {
+    duration.num_microseconds().map(|us| us as f64 / 1000.0)
+}
 
-fn check_sequential_low_deviation(a: u8, b: u8, c: u8) -> bool {
-    let mut numbers = vec![a, b, c];
-    numbers.sort();
-    (numbers[1] - numbers[0]) <= 10 && (numbers[2] - numbers[1]) <= 10
+/// How one configured source fared in `result`.
+enum Status<'a> {
+    Agreed(&'a SourceReading),
+    Disagreed(&'a SourceReading),
+    Failed(&'a SourceError),
 }
 
-fn check_pair_deviation_and_average(a: u8, b: u8, c: u8) -> Option<u8> {
-    if (a as i16 - b as i16).abs() <= 10 {
-        Some(((a as u16 + b as u16) / 2) as u8)
-    } else if (a as i16 - c as i16).abs() <= 10 {
-        Some(((a as u16 + c as u16) / 2) as u8)
-    } else if (b as i16 - c as i16).abs() <= 10 {
-        Some(((b as u16 + c as u16) / 2) as u8)
-    } else {
-        None
+fn status<'a>(result: &'a ConsensusTime, name: &str) -> Option<Status<'a>> {
+    if let Some(reading) = result.readings.iter().find(|r| r.name == name) {
+        return Some(if reading.agreed { Status::Agreed(reading) } else { Status::Disagreed(reading) });
     }
+    let (_, error) = result.errors.iter().find(|(failed, _)| failed == name)?;
+    Some(Status::Failed(error))
 }
 
-// -- Main get_date_time() Function --
+/// A problem `name` had that didn't cost its reading, e.g. a corrected UTC offset.
+fn warning<'a>(result: &'a ConsensusTime, name: &str) -> Option<&'a SourceError> {
+    result.warnings.iter().find(|(warned, _)| warned == name).map(|(_, warning)| warning)
+}
 
-fn get_date_time(fallback_clock: &ClockHandle) -> (u8, u8, u16, u8, u8) {
-    // Wait until fallback is ready
-    while !fallback_clock.is_ready() {
-        thread::sleep(Duration::from_millis(10));
-    }
+fn to_json(consensus: &TimeConsensus, result: &ConsensusTime, zone: Tz) -> Value {
+    let sources: Vec<Value> = consensus
+        .sources()
+        .iter()
+        .map(|source| {
+            let name = source.name();
+            match status(result, name) {
+                Some(Status::Agreed(reading) | Status::Disagreed(reading)) => json!({
+                    "name": name,
+                    "status": if reading.agreed { "agreed" } else { "disagreed" },
+                    "time": rfc3339(reading.time),
+                    "offset_ms": millis(reading.offset),
+                    "uncertainty_ms": reading.uncertainty.as_secs_f64() * 1000.0,
+                    "warning": warning(result, name).map(|w| w.to_string()),
+                }),
+                Some(Status::Failed(error)) => json!({
+                    "name": name,
+                    "status": "failed",
+                    "error": error.to_string(),
+                }),
+                None => json!({"name": name, "status": "unknown"}),
+            }
+        })
+        .collect();
 
-    let time_a = fetch_time_from_url("https://worldtimeapi.org/api/timezone/Europe/London");
-    let time_b = fetch_time_from_url("https://timeapi.io/api/Time/current/zone?timeZone=Europe/London");
-    let time_c = fetch_time_from_url("http://worldclockapi.com/api/json/utc/now"); // Placeholder, might need other API
+    json!({
+        "time": rfc3339(result.time),
+        "zoned": {
+            "zone": zone.name(),
+            "time": rfc3339(result.in_zone(zone)),
+            "abbreviation": result.in_zone(zone).format("%Z").to_string(),
+        },
+        "unix": result.time.timestamp_millis() as f64 / 1000.0,
+        "path": result.path.to_string(),
+        "used_fallback": result.used_fallback,
+        "error_bound_ms": result.error_bound.map(|bound| bound.as_secs_f64() * 1000.0),
+        "interval": result.interval.map(|interval| json!({
+            "earliest": rfc3339(interval.earliest),
+            "latest": rfc3339(interval.latest),
+        })),
+        "sources": sources,
+    })
+}
 
-    if let (Some(a), Some(b), Some(c)) = (time_a, time_b, time_c) {
-        if a == b && b == c {
-            return a;
-        }
+/// Logs to stderr, at the level in `RUST_LOG` if set.
+fn init_logging(verbose: bool) {
+    let default = if verbose { "sybau=debug" } else { "warn" };
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default));
+    tracing_subscriber::fmt().with_env_filter(filter).with_writer(std::io::stderr).init();
+}
 
-        if a.0 == b.0 && b.0 == c.0 && // day
-           a.1 == b.1 && b.1 == c.1 && // month
-           a.2 == b.2 && b.2 == c.2 && // year
-           a.3 == b.3 && b.3 == c.3     // hour
-        {
-            if check_sequential_low_deviation(a.4, b.4, c.4) {
-                let avg_min = ((a.4 as u16 + b.4 as u16 + c.4 as u16) / 3) as u8;
-                return (a.0, a.1, a.2, a.3, avg_min);
+/// Lists every source with whether it agreed, disagreed or failed, on stderr.
+fn report(consensus: &TimeConsensus, result: &ConsensusTime) {
+    eprintln!("agreement: {}", result.path);
+    for source in consensus.sources() {
+        let name = source.name();
+        match status(result, name) {
+            Some(Status::Agreed(reading) | Status::Disagreed(reading)) => {
+                let verdict = if reading.agreed { "agreed" } else { "disagreed" };
+                match warning(result, name) {
+                    Some(warning) => {
+                        eprintln!("  {name}: {verdict}, offset {}ms ({warning})", reading.offset.num_milliseconds())
+                    }
+                    None => eprintln!("  {name}: {verdict}, offset {}ms", reading.offset.num_milliseconds()),
+                }
             }
-        }
-
-        if let Some(avg_minute) = check_pair_deviation_and_average(a.4, b.4, c.4) {
-            return (a.0, a.1, a.2, a.3, avg_minute);
+            Some(Status::Failed(error)) => eprintln!("  {name}: {error}"),
+            None => eprintln!("  {name}: unknown"),
         }
     }
-
-    let fallback = fallback_clock.get_time();
-    println!("[Fallback] Using software clock");
-    (fallback.day, fallback.month, fallback.year, fallback.hour, fallback.min)
 }
 
-fn main() {
-    let clock_handle = ClockHandle::start();
-    let datetime = get_date_time(&clock_handle);
+fn watch(config: &Config, zone: Tz) {
+    let service = TimeService::from_config(config);
+    let events = service.subscribe();
+    service.start();
 
-    println!(
-        "Final UTC Time: {:02}/{:02}/{:04} {:02}:{:02}Z",
-        datetime.0, datetime.1, datetime.2, datetime.3, datetime.4
-    );
+    for event in events {
+        let now = rfc3339(service.now().with_timezone(&zone));
+        match event {
+            TimeEvent::OffsetChanged { offset, .. } => {
+                println!("{now} offset {}ms", offset.num_milliseconds());
+            }
+            TimeEvent::FellBack(_) => println!("{now} sources disagree, using software clock"),
+            TimeEvent::Recovered(result) => println!("{now} sources recovered ({})", result.path),
+        }
+    }
 }
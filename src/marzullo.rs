@@ -0,0 +1,77 @@
+use chrono::{DateTime, Utc};
+
+// -- Marzullo's Algorithm --
+
+/// A closed range of instants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeInterval {
+    pub earliest: DateTime<Utc>,
+    pub latest: DateTime<Utc>,
+}
+
+impl TimeInterval {
+    pub fn new(earliest: DateTime<Utc>, latest: DateTime<Utc>) -> Self {
+        Self { earliest, latest }
+    }
+
+    /// `center ± radius`.
+    pub fn around(center: DateTime<Utc>, radius: chrono::Duration) -> Self {
+        Self::new(center - radius.abs(), center + radius.abs())
+    }
+
+    pub fn midpoint(&self) -> DateTime<Utc> {
+        self.earliest + (self.latest - self.earliest) / 2
+    }
+
+    pub fn contains(&self, time: DateTime<Utc>) -> bool {
+        self.earliest <= time && time <= self.latest
+    }
+
+    /// Whether `other` lies entirely inside this interval.
+    pub fn covers(&self, other: &TimeInterval) -> bool {
+        self.contains(other.earliest) && self.contains(other.latest)
+    }
+}
+
+/// The range consistent with the largest number of source intervals.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Intersection {
+    pub interval: TimeInterval,
+    /// Indices of the intervals that cover `interval`.
+    pub members: Vec<usize>,
+}
+
+/// Finds the smallest interval consistent with as many of `intervals` as possible.
+///
+/// Touching intervals count as overlapping. When two disjoint regions are
+/// backed by the same number of sources the earlier one wins, so callers that
+/// care should require a strict majority of `members`.
+pub fn marzullo(intervals: &[TimeInterval]) -> Option<Intersection> {
+    // Starts sort before ends at the same instant.
+    let mut edges: Vec<(DateTime<Utc>, i8)> = intervals
+        .iter()
+        .flat_map(|i| [(i.earliest, -1), (i.latest, 1)])
+        .collect();
+    edges.sort();
+
+    let mut best = 0;
+    let mut count = 0;
+    let mut found = None;
+    for (k, &(at, kind)) in edges.iter().enumerate() {
+        count -= kind as i32;
+        if count > best {
+            best = count;
+            // The region runs until the next edge, which is always an end here.
+            found = Some(TimeInterval::new(at, edges[k + 1].0));
+        }
+    }
+
+    let interval = found?;
+    let members = intervals
+        .iter()
+        .enumerate()
+        .filter(|(_, i)| i.covers(&interval))
+        .map(|(k, _)| k)
+        .collect();
+    Some(Intersection { interval, members })
+}
@@ -0,0 +1,104 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::trust::TrustPolicy;
+
+/// How the agreed time is picked from the agreeing readings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Selection {
+    /// Weighted mean of the readings.
+    #[default]
+    Mean,
+    /// Weighted median of the readings; a single outlier inside the agreed
+    /// interval can't pull the result.
+    Median,
+}
+
+/// The rules a consensus run must satisfy before its answer is trusted.
+///
+/// ```toml
+/// [policy]
+/// min_quorum = 3
+/// max_spread_secs = 2
+/// tolerance_ms = 500
+/// selection = "median"
+/// deadline_ms = 3000
+/// horizon_days = 3650
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConsensusPolicy {
+    /// How many sources must agree. `None` means a strict majority of the
+    /// sources queried; a smaller fixed quorum lets two disjoint camps of
+    /// equal size both qualify, in which case the earlier one wins.
+    ///
+    /// Without a fixed quorum, runs where too few sources answer fall to the
+    /// degraded tiers of [`AgreementPath`](crate::AgreementPath) before the software clock.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_quorum: Option<usize>,
+    /// The agreeing readings may be at most this far apart.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_spread_secs: Option<u64>,
+    /// How far sources may disagree beyond their own measured uncertainty.
+    pub tolerance_ms: u64,
+    pub selection: Selection,
+    /// How long to wait for the sources, all queried at once. Whoever hasn't
+    /// answered by then is left out of this round.
+    pub deadline_ms: u64,
+    /// Times more than this long after the build are rejected as absurd,
+    /// from sources and the software clock alike.
+    pub horizon_days: u64,
+    pub trust: TrustPolicy,
+}
+
+impl Default for ConsensusPolicy {
+    fn default() -> Self {
+        Self {
+            min_quorum: None,
+            max_spread_secs: None,
+            tolerance_ms: 1_000,
+            selection: Selection::default(),
+            deadline_ms: 10_000,
+            horizon_days: 36_525,
+            trust: TrustPolicy::default(),
+        }
+    }
+}
+
+impl ConsensusPolicy {
+    /// The number of agreeing sources required out of `queried`.
+    pub fn quorum(&self, queried: usize) -> usize {
+        self.min_quorum.unwrap_or(queried / 2 + 1)
+    }
+
+    pub fn max_spread(&self) -> Option<Duration> {
+        self.max_spread_secs.map(Duration::from_secs)
+    }
+
+    pub fn tolerance(&self) -> Duration {
+        Duration::from_millis(self.tolerance_ms)
+    }
+
+    pub fn deadline(&self) -> Duration {
+        Duration::from_millis(self.deadline_ms)
+    }
+
+    /// The latest believable time: the build time plus the horizon.
+    pub fn not_after(&self) -> DateTime<Utc> {
+        let horizon = chrono::Duration::try_days(self.horizon_days.min(i64::MAX as u64) as i64);
+        horizon.and_then(|h| build_time().checked_add_signed(h)).unwrap_or(DateTime::<Utc>::MAX_UTC)
+    }
+}
+
+/// When this copy of sybau was built, or `SOURCE_DATE_EPOCH` if that was set.
+/// Nothing earlier is believed by default, since it has already happened.
+pub fn build_time() -> DateTime<Utc> {
+    env!("SYBAU_BUILD_TIME")
+        .parse()
+        .ok()
+        .and_then(|secs| DateTime::from_timestamp(secs, 0))
+        .unwrap_or(DateTime::UNIX_EPOCH)
+}
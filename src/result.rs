@@ -0,0 +1,114 @@
+use std::fmt;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+
+use crate::marzullo::TimeInterval;
+use crate::source::SourceError;
+
+/// How the consensus arrived at its answer, from most to least trustworthy.
+///
+/// [`Majority`](AgreementPath::Majority) and
+/// [`SingleSource`](AgreementPath::SingleSource) are degraded tiers for when
+/// sources fail to answer. They are only tried when the policy doesn't fix
+/// `min_quorum`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AgreementPath {
+    /// Every source's interval overlapped the agreed one.
+    Unanimous,
+    /// Enough sources to meet the policy's quorum overlapped the agreed interval, but not all.
+    Quorum,
+    /// Too few sources answered to meet the quorum, but at least two of them,
+    /// and a strict majority of those that answered, agree.
+    Majority,
+    /// Only one source answered, and the software clock lies within its interval.
+    SingleSource,
+    /// No agreement could be reached and the software clock was used.
+    Fallback,
+}
+
+impl AgreementPath {
+    /// Whether fewer sources than the quorum vouch for the time.
+    pub fn is_degraded(&self) -> bool {
+        matches!(self, AgreementPath::Majority | AgreementPath::SingleSource | AgreementPath::Fallback)
+    }
+}
+
+impl fmt::Display for AgreementPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            AgreementPath::Unanimous => "unanimous",
+            AgreementPath::Quorum => "quorum",
+            AgreementPath::Majority => "majority",
+            AgreementPath::SingleSource => "single-source",
+            AgreementPath::Fallback => "fallback",
+        };
+        f.write_str(name)
+    }
+}
+
+/// What one source reported in a consensus run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceReading {
+    pub name: String,
+    /// The source's time when its answer arrived.
+    pub time: DateTime<Utc>,
+    /// Source time minus local time.
+    pub offset: chrono::Duration,
+    /// The true offset lies within `offset ± uncertainty`.
+    pub uncertainty: Duration,
+    /// Whether the reading was inside the agreed interval.
+    pub agreed: bool,
+}
+
+/// The outcome of a consensus run.
+#[derive(Debug, Clone)]
+pub struct ConsensusTime {
+    /// The agreed instant, with full sub-second precision.
+    pub time: DateTime<Utc>,
+    /// Sources whose readings contributed to `time`.
+    pub sources: Vec<String>,
+    /// Which agreement rule produced `time`.
+    pub path: AgreementPath,
+    /// The range every contributing source agrees true UTC lies in.
+    pub interval: Option<TimeInterval>,
+    /// Estimated maximum distance between `time` and true UTC, if known.
+    pub error_bound: Option<Duration>,
+    /// Whether `time` came from the software clock rather than the network.
+    pub used_fallback: bool,
+    /// Every reading received, in source order.
+    pub readings: Vec<SourceReading>,
+    /// Sources that gave no reading, and why, in source order.
+    pub errors: Vec<(String, SourceError)>,
+    /// Problems with readings that were still used, such as a
+    /// [`SourceError::OffsetMismatch`], in source order.
+    pub warnings: Vec<(String, SourceError)>,
+}
+
+impl ConsensusTime {
+    /// The agreed instant as wall-clock time in `zone`, with that zone's DST
+    /// rules and abbreviations.
+    pub fn in_zone(&self, zone: Tz) -> DateTime<Tz> {
+        self.time.with_timezone(&zone)
+    }
+
+    pub(crate) fn fallback(time: DateTime<Utc>) -> Self {
+        Self {
+            time,
+            sources: Vec::new(),
+            path: AgreementPath::Fallback,
+            interval: None,
+            error_bound: None,
+            used_fallback: true,
+            readings: Vec::new(),
+            errors: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+}
+
+/// The system's IANA time zone, if it can be determined and is in the tz database.
+pub fn system_zone() -> Option<Tz> {
+    iana_time_zone::get_timezone().ok()?.parse().ok()
+}
@@ -0,0 +1,287 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::{mpsc, Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::consensus::TimeConsensus;
+use crate::result::ConsensusTime;
+
+/// When a [`TimeService`] re-runs consensus.
+///
+/// ```toml
+/// [resync]
+/// interval_secs = 300
+/// jitter_ms = 10000
+/// retry_secs = 5
+/// max_backoff_secs = 300
+/// offset_threshold_ms = 100
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResyncPolicy {
+    /// Time between runs while the sources agree.
+    pub interval_secs: u64,
+    /// Up to this much is added to each wait, so that many clients started
+    /// together don't query the sources in lockstep.
+    pub jitter_ms: u64,
+    /// Wait after the first run that falls back; doubled for each further one.
+    pub retry_secs: u64,
+    /// The longest wait between runs that fall back.
+    pub max_backoff_secs: u64,
+    /// Smallest change in offset reported as [`TimeEvent::OffsetChanged`].
+    pub offset_threshold_ms: u64,
+}
+
+impl Default for ResyncPolicy {
+    fn default() -> Self {
+        Self {
+            interval_secs: 300,
+            jitter_ms: 10_000,
+            retry_secs: 5,
+            max_backoff_secs: 300,
+            offset_threshold_ms: 100,
+        }
+    }
+}
+
+impl ResyncPolicy {
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_secs)
+    }
+
+    pub fn jitter(&self) -> Duration {
+        Duration::from_millis(self.jitter_ms)
+    }
+
+    pub fn offset_threshold(&self) -> Duration {
+        Duration::from_millis(self.offset_threshold_ms)
+    }
+
+    /// The wait before the next run, without jitter, after `failures`
+    /// consecutive runs that fell back.
+    pub fn backoff(&self, failures: u32) -> Duration {
+        if failures == 0 {
+            return self.interval();
+        }
+        let factor = 1u64.checked_shl(failures - 1).unwrap_or(u64::MAX);
+        Duration::from_secs(self.retry_secs.saturating_mul(factor).min(self.max_backoff_secs))
+    }
+}
+
+/// Something a [`TimeService`] noticed while resyncing.
+#[derive(Debug, Clone)]
+pub enum TimeEvent {
+    /// The agreed time moved relative to the system clock by at least the
+    /// policy's threshold since the last report, or this is the first agreement.
+    /// Offsets are agreed time minus system time.
+    OffsetChanged {
+        previous: Option<chrono::Duration>,
+        offset: chrono::Duration,
+    },
+    /// The sources stopped agreeing; time now comes from the software clock.
+    FellBack(ConsensusTime),
+    /// The sources agree again after a fallback.
+    Recovered(ConsensusTime),
+}
+
+enum Subscriber {
+    Channel(mpsc::Sender<TimeEvent>),
+    Callback(Box<dyn Fn(&TimeEvent) + Send>),
+}
+
+impl Subscriber {
+    /// Delivers `event`, returning false once the subscriber has gone away.
+    fn deliver(&self, event: &TimeEvent) -> bool {
+        match self {
+            Subscriber::Channel(tx) => tx.send(event.clone()).is_ok(),
+            Subscriber::Callback(f) => {
+                f(event);
+                true
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct State {
+    stopped: bool,
+    resync_requested: bool,
+    last: Option<ConsensusTime>,
+    /// The offset last reported in [`TimeEvent::OffsetChanged`].
+    reported_offset: Option<chrono::Duration>,
+    fell_back: bool,
+}
+
+struct Shared {
+    consensus: TimeConsensus,
+    resync: ResyncPolicy,
+    state: Mutex<State>,
+    wake: Condvar,
+    subscribers: Mutex<Vec<Subscriber>>,
+}
+
+/// Keeps re-running consensus in the background and hands out the disciplined
+/// time without touching the network.
+///
+/// Subscribers are notified on the service's own thread, so callbacks should
+/// return quickly. The service stops when dropped.
+pub struct TimeService {
+    shared: Arc<Shared>,
+    worker: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl TimeService {
+    /// A service that hasn't started yet; subscribe first, then [`TimeService::start`].
+    pub fn new(consensus: TimeConsensus, resync: ResyncPolicy) -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                consensus,
+                resync,
+                state: Mutex::new(State::default()),
+                wake: Condvar::new(),
+                subscribers: Mutex::new(Vec::new()),
+            }),
+            worker: Mutex::new(None),
+        }
+    }
+
+    /// Uses the sources, policy and resync policy of `config`.
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(TimeConsensus::from_config(config), config.resync)
+    }
+
+    /// Starts resyncing in the background, beginning immediately. Does nothing
+    /// if already started.
+    pub fn start(&self) {
+        let mut worker = self.worker.lock().unwrap();
+        if worker.is_none() {
+            let shared = Arc::clone(&self.shared);
+            *worker = Some(thread::spawn(move || shared.run()));
+        }
+    }
+
+    /// Stops resyncing and waits for a run in progress to finish.
+    pub fn stop(&self) {
+        self.shared.state.lock().unwrap().stopped = true;
+        self.shared.wake.notify_all();
+        if let Some(worker) = self.worker.lock().unwrap().take() {
+            let _ = worker.join();
+        }
+    }
+
+    /// Cuts the current wait short and resyncs now.
+    pub fn resync_now(&self) {
+        self.shared.state.lock().unwrap().resync_requested = true;
+        self.shared.wake.notify_all();
+    }
+
+    /// The current time from the disciplined software clock.
+    pub fn now(&self) -> DateTime<Utc> {
+        self.shared.consensus.fallback_clock().now()
+    }
+
+    /// The outcome of the most recent run, if any has finished.
+    pub fn last_sync(&self) -> Option<ConsensusTime> {
+        self.shared.state.lock().unwrap().last.clone()
+    }
+
+    pub fn consensus(&self) -> &TimeConsensus {
+        &self.shared.consensus
+    }
+
+    pub fn resync_policy(&self) -> &ResyncPolicy {
+        &self.shared.resync
+    }
+
+    /// A channel that receives every event from now on.
+    pub fn subscribe(&self) -> mpsc::Receiver<TimeEvent> {
+        let (tx, rx) = mpsc::channel();
+        self.shared.subscribers.lock().unwrap().push(Subscriber::Channel(tx));
+        rx
+    }
+
+    /// Calls `f` with every event from now on.
+    pub fn on_event(&self, f: impl Fn(&TimeEvent) + Send + 'static) {
+        self.shared.subscribers.lock().unwrap().push(Subscriber::Callback(Box::new(f)));
+    }
+}
+
+impl Drop for TimeService {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+impl Shared {
+    fn run(&self) {
+        let mut failures = 0u32;
+        loop {
+            let result = self.consensus.get_date_time();
+            failures = if result.used_fallback { failures.saturating_add(1) } else { 0 };
+            let events = self.observe(result);
+            self.publish(&events);
+
+            let wait = self.resync.backoff(failures) + jitter(self.resync.jitter());
+            tracing::debug!(wait_ms = wait.as_millis() as u64, failures, "next resync scheduled");
+            let state = self.state.lock().unwrap();
+            let (mut state, _) = self
+                .wake
+                .wait_timeout_while(state, wait, |s| !s.stopped && !s.resync_requested)
+                .unwrap();
+            if state.stopped {
+                return;
+            }
+            state.resync_requested = false;
+        }
+    }
+
+    /// Records `result` and works out which events it raises.
+    fn observe(&self, result: ConsensusTime) -> Vec<TimeEvent> {
+        let mut state = self.state.lock().unwrap();
+        let mut events = Vec::new();
+
+        if result.used_fallback {
+            if !state.fell_back {
+                state.fell_back = true;
+                events.push(TimeEvent::FellBack(result.clone()));
+            }
+        } else {
+            if state.fell_back {
+                state.fell_back = false;
+                events.push(TimeEvent::Recovered(result.clone()));
+            }
+            let offset = result.time - Utc::now();
+            let threshold = chrono::Duration::from_std(self.resync.offset_threshold()).unwrap_or(chrono::Duration::MAX);
+            let moved = state.reported_offset.is_none_or(|previous| (offset - previous).abs() >= threshold);
+            if moved {
+                events.push(TimeEvent::OffsetChanged { previous: state.reported_offset, offset });
+                state.reported_offset = Some(offset);
+            }
+        }
+
+        state.last = Some(result);
+        events
+    }
+
+    fn publish(&self, events: &[TimeEvent]) {
+        let mut subscribers = self.subscribers.lock().unwrap();
+        for event in events {
+            subscribers.retain(|s| s.deliver(event));
+        }
+    }
+}
+
+/// A random duration up to `max`.
+fn jitter(max: Duration) -> Duration {
+    let millis = max.as_millis() as u64;
+    if millis == 0 {
+        return Duration::ZERO;
+    }
+    let random = RandomState::new().build_hasher().finish();
+    Duration::from_millis(random % (millis + 1))
+}
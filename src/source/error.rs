@@ -0,0 +1,159 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+use chrono::{DateTime, FixedOffset, Utc};
+use chrono_tz::Tz;
+
+/// Why a source produced no reading.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceError {
+    /// The host name didn't resolve.
+    Dns(String),
+    /// The TLS handshake failed, e.g. on an untrusted certificate.
+    Tls(String),
+    /// The connection was refused, reset or otherwise failed.
+    Connect(String),
+    /// No answer within the source's timeout.
+    Timeout,
+    /// The source wasn't queried in time, or its answer came after the consensus deadline.
+    MissedDeadline,
+    /// The server answered with a 4xx or 5xx status.
+    HttpStatus(u16),
+    /// The response body couldn't be read or isn't JSON.
+    Body(String),
+    /// The response lacks the expected fields, e.g. JSON of the wrong shape
+    /// or no `Date` header.
+    Schema(String),
+    /// The timestamp was found but couldn't be parsed.
+    Parse(String),
+    /// An SNTP reply was malformed or refused, e.g. a kiss-o'-death.
+    Protocol(String),
+    /// The source's address is invalid, or a socket couldn't be set up.
+    Address(String),
+    /// The source's UTC offset disagrees with the tz database. Unlike the
+    /// other variants this doesn't cost the reading: the time is corrected and
+    /// the mismatch reported in [`ConsensusTime::warnings`](crate::ConsensusTime::warnings).
+    OffsetMismatch(OffsetMismatch),
+    /// The source's time is earlier than the last time sybau trusted, as kept
+    /// in its [`StateFile`](crate::StateFile).
+    BeforeFloor { time: DateTime<Utc>, floor: DateTime<Utc> },
+    /// The source's time is before the build or past the policy's horizon,
+    /// and can't be right.
+    OutOfRange { time: DateTime<Utc>, not_before: DateTime<Utc>, not_after: DateTime<Utc> },
+    /// Anything else, e.g. from a custom [`TimeSource`](crate::TimeSource).
+    Other(String),
+}
+
+/// A provider stamped its local time with an offset the tz database doesn't
+/// give for that zone, e.g. because its own tz data predates a DST change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OffsetMismatch {
+    pub zone: Tz,
+    /// The offset in the provider's timestamp.
+    pub reported: FixedOffset,
+    /// The offset the tz database gives for the provider's local time, or for
+    /// the instant if the zone skipped that local time.
+    pub expected: FixedOffset,
+}
+
+impl fmt::Display for OffsetMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "offset {} for {}, the tz database says {}", self.reported, self.zone, self.expected)
+    }
+}
+
+impl fmt::Display for SourceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SourceError::Dns(e) => write!(f, "cannot resolve host: {e}"),
+            SourceError::Tls(e) => write!(f, "TLS failure: {e}"),
+            SourceError::Connect(e) => write!(f, "cannot connect: {e}"),
+            SourceError::Timeout => f.write_str("timed out"),
+            SourceError::MissedDeadline => f.write_str("no answer before the consensus deadline"),
+            SourceError::HttpStatus(status) => write!(f, "HTTP status {status}"),
+            SourceError::Body(e) => write!(f, "unreadable response: {e}"),
+            SourceError::Schema(e) => write!(f, "unexpected response: {e}"),
+            SourceError::Parse(e) => write!(f, "unparseable timestamp: {e}"),
+            SourceError::Protocol(e) => write!(f, "bad SNTP reply: {e}"),
+            SourceError::Address(e) => write!(f, "bad address: {e}"),
+            SourceError::OffsetMismatch(m) => write!(f, "corrected UTC offset: {m}"),
+            SourceError::BeforeFloor { time, floor } => {
+                write!(f, "reported {time}, before the last trusted time {floor}")
+            }
+            SourceError::OutOfRange { time, not_before, not_after } => {
+                write!(f, "reported {time}, outside {not_before} to {not_after}")
+            }
+            SourceError::Other(e) => f.write_str(e),
+        }
+    }
+}
+
+impl Error for SourceError {}
+
+impl From<reqwest::Error> for SourceError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            return SourceError::Timeout;
+        }
+        if let Some(status) = e.status() {
+            return SourceError::HttpStatus(status.as_u16());
+        }
+        if e.is_decode() || e.is_body() {
+            return SourceError::Body(chain(&e));
+        }
+        if e.is_builder() {
+            return SourceError::Address(chain(&e));
+        }
+
+        // reqwest doesn't tell resolver and TLS failures apart from other
+        // connection errors, but their causes say what they are.
+        let message = chain(&e);
+        let lower = message.to_lowercase();
+        if lower.contains("dns error") || lower.contains("failed to lookup address") {
+            SourceError::Dns(message)
+        } else if ["certificate", "tls", "ssl", "handshake"].iter().any(|k| lower.contains(k)) {
+            SourceError::Tls(message)
+        } else {
+            SourceError::Connect(message)
+        }
+    }
+}
+
+impl From<serde_json::Error> for SourceError {
+    fn from(e: serde_json::Error) -> Self {
+        SourceError::Schema(e.to_string())
+    }
+}
+
+impl From<chrono::ParseError> for SourceError {
+    fn from(e: chrono::ParseError) -> Self {
+        SourceError::Parse(e.to_string())
+    }
+}
+
+impl From<io::Error> for SourceError {
+    fn from(e: io::Error) -> Self {
+        match e.kind() {
+            // A socket read timeout surfaces as either, depending on the platform.
+            io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => SourceError::Timeout,
+            io::ErrorKind::AddrNotAvailable | io::ErrorKind::AddrInUse => SourceError::Address(e.to_string()),
+            _ => SourceError::Connect(e.to_string()),
+        }
+    }
+}
+
+/// `e` and its causes, joined with ": ".
+fn chain(e: &dyn Error) -> String {
+    let mut message = e.to_string();
+    let mut cause = e.source();
+    while let Some(e) = cause {
+        let text = e.to_string();
+        if !message.contains(&text) {
+            message.push_str(": ");
+            message.push_str(&text);
+        }
+        cause = e.source();
+    }
+    message
+}
@@ -0,0 +1,86 @@
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use reqwest::header::{HeaderMap, DATE};
+
+use super::{head, SourceError, TimeSample, TimeSource};
+#[cfg(feature = "async")]
+use super::{head_async, AsyncTimeSource, BoxFuture};
+use crate::config::SourceConfig;
+
+// -- HTTP Date header --
+
+/// Reads the `Date` header of any HTTP(S) server.
+///
+/// The header only carries whole seconds, so each sample is half a second
+/// less certain than the round trip alone would make it. Ordinary well-known
+/// sites are good enough to build a quorum when the time APIs are down.
+pub struct HttpDate {
+    source: SourceConfig,
+}
+
+impl HttpDate {
+    pub fn new(source: SourceConfig) -> Self {
+        Self { source }
+    }
+}
+
+impl TimeSource for HttpDate {
+    fn name(&self) -> &str {
+        &self.source.url
+    }
+
+    fn weight(&self) -> f64 {
+        self.source.weight
+    }
+
+    fn fetch(&self) -> Result<TimeSample, SourceError> {
+        let (headers, exchange) = head(&self.source)?;
+        let server = date_header(&headers)?;
+        Ok(TimeSample::from_exchange(exchange.sent, exchange.rtt, server).with_resolution(Duration::from_secs(1)))
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncTimeSource for HttpDate {
+    fn name(&self) -> &str {
+        &self.source.url
+    }
+
+    fn weight(&self) -> f64 {
+        self.source.weight
+    }
+
+    fn fetch(&self) -> BoxFuture<'_, Result<TimeSample, SourceError>> {
+        Box::pin(async move {
+            let (headers, exchange) = head_async(&self.source).await?;
+            let server = date_header(&headers)?;
+            Ok(TimeSample::from_exchange(exchange.sent, exchange.rtt, server).with_resolution(Duration::from_secs(1)))
+        })
+    }
+}
+
+fn date_header(headers: &HeaderMap) -> Result<DateTime<Utc>, SourceError> {
+    let value = headers
+        .get(DATE)
+        .ok_or_else(|| SourceError::Schema("no Date header".to_string()))?;
+    let text = value
+        .to_str()
+        .map_err(|_| SourceError::Parse("Date header is not ASCII".to_string()))?;
+    let date = parse_http_date(text).ok_or_else(|| SourceError::Parse(format!("Date header {text:?} is not an HTTP-date")))?;
+    tracing::trace!(header = text, %date, "parsed Date header");
+    Ok(date)
+}
+
+/// Parses an HTTP-date in any of the three forms RFC 7231 §7.1.1.1 requires
+/// recipients to accept: IMF-fixdate, RFC 850 and asctime.
+pub fn parse_http_date(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(imf) = DateTime::parse_from_rfc2822(value) {
+        return Some(imf.with_timezone(&Utc));
+    }
+    ["%A, %d-%b-%y %H:%M:%S GMT", "%a %b %e %H:%M:%S %Y"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .map(|naive| naive.and_utc())
+}
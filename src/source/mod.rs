@@ -0,0 +1,225 @@
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+
+use crate::config::{ParserKind, SourceConfig};
+
+mod error;
+mod http_date;
+mod providers;
+mod sntp;
+
+pub use error::{OffsetMismatch, SourceError};
+pub use http_date::{parse_http_date, HttpDate};
+pub use providers::{JsonPointer, TimeApiIo, WorldClockApi, WorldTimeApi};
+pub use sntp::{from_ntp, to_ntp, Sntp, SntpSample};
+
+// -- Online Time Source Fetching --
+
+/// The providers queried when no other sources are given.
+pub const DEFAULT_SOURCES: [(&str, ParserKind); 3] = [
+    ("https://worldtimeapi.org/api/timezone/Europe/London", ParserKind::WorldTimeApi),
+    ("https://timeapi.io/api/Time/current/zone?timeZone=UTC", ParserKind::TimeApiIo),
+    ("http://worldclockapi.com/api/json/utc/now", ParserKind::WorldClockApi),
+];
+
+/// One source's reading, expressed relative to the local clock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeSample {
+    /// Local clock reading when the response arrived.
+    pub local: DateTime<Utc>,
+    /// Source time minus local time.
+    pub offset: chrono::Duration,
+    /// The true offset lies within `offset ± uncertainty`.
+    pub uncertainty: Duration,
+    /// Set when the source's own UTC offset was wrong and `offset` is based
+    /// on the corrected time.
+    pub offset_mismatch: Option<OffsetMismatch>,
+}
+
+impl TimeSample {
+    /// The source's time as of [`TimeSample::local`].
+    pub fn time(&self) -> DateTime<Utc> {
+        self.local + self.offset
+    }
+
+    /// Builds a sample from a request/response exchange, assuming `server`
+    /// was stamped halfway between `sent` and `sent + rtt` (±RTT/2).
+    pub fn from_exchange(sent: DateTime<Utc>, rtt: Duration, server: DateTime<Utc>) -> Self {
+        let rtt = chrono::Duration::from_std(rtt).unwrap_or(chrono::Duration::MAX);
+        let midpoint = sent + rtt / 2;
+        Self {
+            local: sent + rtt,
+            offset: server - midpoint,
+            uncertainty: (rtt / 2).to_std().unwrap_or_default(),
+            offset_mismatch: None,
+        }
+    }
+
+    /// Accounts for a server timestamp truncated to `resolution` (e.g. whole
+    /// seconds): the true value lies somewhere in the following `resolution`.
+    pub fn with_resolution(self, resolution: Duration) -> Self {
+        let half = resolution / 2;
+        Self {
+            offset: self.offset + chrono::Duration::from_std(half).unwrap_or_default(),
+            uncertainty: self.uncertainty + half,
+            ..self
+        }
+    }
+}
+
+/// A boxed, sendable future, as returned by [`AsyncTimeSource::fetch`].
+#[cfg(feature = "async")]
+pub type BoxFuture<'a, T> = std::pin::Pin<Box<dyn std::future::Future<Output = T> + Send + 'a>>;
+
+/// Something that can report the current UTC time.
+pub trait TimeSource: Send + Sync {
+    /// Identifies the source in results and diagnostics; usually its URL.
+    fn name(&self) -> &str;
+
+    /// Relative influence on the averaged time.
+    fn weight(&self) -> f64 {
+        1.0
+    }
+
+    fn fetch(&self) -> Result<TimeSample, SourceError>;
+}
+
+/// The async counterpart of [`TimeSource`]. Every built-in source implements both.
+#[cfg(feature = "async")]
+pub trait AsyncTimeSource: Send + Sync {
+    /// Identifies the source in results and diagnostics; usually its URL.
+    fn name(&self) -> &str;
+
+    /// Relative influence on the averaged time.
+    fn weight(&self) -> f64 {
+        1.0
+    }
+
+    fn fetch(&self) -> BoxFuture<'_, Result<TimeSample, SourceError>>;
+}
+
+/// Runs a blocking [`TimeSource`] on tokio's blocking pool, for sources that
+/// have no native async implementation.
+#[cfg(feature = "async")]
+pub struct Blocking<S>(pub std::sync::Arc<S>);
+
+#[cfg(feature = "async")]
+impl<S: TimeSource + 'static> AsyncTimeSource for Blocking<S> {
+    fn name(&self) -> &str {
+        self.0.name()
+    }
+
+    fn weight(&self) -> f64 {
+        self.0.weight()
+    }
+
+    fn fetch(&self) -> BoxFuture<'_, Result<TimeSample, SourceError>> {
+        let source = std::sync::Arc::clone(&self.0);
+        Box::pin(async move {
+            tokio::task::spawn_blocking(move || source.fetch())
+                .await
+                .unwrap_or_else(|e| Err(SourceError::Other(e.to_string())))
+        })
+    }
+}
+
+impl SourceConfig {
+    /// The [`TimeSource`] that reads this source's responses.
+    pub fn build(&self) -> Box<dyn TimeSource> {
+        match self.parser {
+            ParserKind::WorldTimeApi => Box::new(WorldTimeApi::new(self.clone())),
+            ParserKind::TimeApiIo => Box::new(TimeApiIo::new(self.clone())),
+            ParserKind::WorldClockApi => Box::new(WorldClockApi::new(self.clone())),
+            ParserKind::JsonPointer => Box::new(JsonPointer::new(self.clone())),
+            ParserKind::Sntp => Box::new(Sntp::new(self.clone())),
+            ParserKind::HttpDate => Box::new(HttpDate::new(self.clone())),
+        }
+    }
+
+    /// The [`AsyncTimeSource`] that reads this source's responses.
+    #[cfg(feature = "async")]
+    pub fn build_async(&self) -> Box<dyn AsyncTimeSource> {
+        match self.parser {
+            ParserKind::WorldTimeApi => Box::new(WorldTimeApi::new(self.clone())),
+            ParserKind::TimeApiIo => Box::new(TimeApiIo::new(self.clone())),
+            ParserKind::WorldClockApi => Box::new(WorldClockApi::new(self.clone())),
+            ParserKind::JsonPointer => Box::new(JsonPointer::new(self.clone())),
+            ParserKind::Sntp => Box::new(Sntp::new(self.clone())),
+            ParserKind::HttpDate => Box::new(HttpDate::new(self.clone())),
+        }
+    }
+}
+
+/// Queries a worldtimeapi.org-style URL.
+pub fn fetch_time_from_url(url: &str) -> Result<DateTime<Utc>, SourceError> {
+    TimeSource::fetch(&WorldTimeApi::new(SourceConfig::new(url))).map(|sample| sample.time())
+}
+
+/// When an HTTP request went out and how long the full exchange took.
+struct Exchange {
+    sent: DateTime<Utc>,
+    rtt: Duration,
+}
+
+fn client(source: &SourceConfig) -> Result<reqwest::blocking::Client, SourceError> {
+    Ok(reqwest::blocking::Client::builder().timeout(source.timeout()).build()?)
+}
+
+/// GETs the source's URL within its timeout and returns the body as JSON.
+fn get_json(source: &SourceConfig) -> Result<(serde_json::Value, Exchange), SourceError> {
+    let client = client(source)?;
+
+    let sent = Utc::now();
+    let started = Instant::now();
+    let response = client.get(&source.url).send()?.error_for_status()?;
+    let body = response.json()?;
+    let rtt = started.elapsed();
+
+    Ok((body, Exchange { sent, rtt }))
+}
+
+/// HEADs the source's URL within its timeout and returns the response headers,
+/// whatever the status: error pages carry a `Date` too.
+fn head(source: &SourceConfig) -> Result<(reqwest::header::HeaderMap, Exchange), SourceError> {
+    let client = client(source)?;
+
+    let sent = Utc::now();
+    let started = Instant::now();
+    let response = client.head(&source.url).send()?;
+    let rtt = started.elapsed();
+
+    Ok((response.headers().clone(), Exchange { sent, rtt }))
+}
+
+#[cfg(feature = "async")]
+fn async_client(source: &SourceConfig) -> Result<reqwest::Client, SourceError> {
+    Ok(reqwest::Client::builder().timeout(source.timeout()).build()?)
+}
+
+/// Async [`get_json`].
+#[cfg(feature = "async")]
+async fn get_json_async(source: &SourceConfig) -> Result<(serde_json::Value, Exchange), SourceError> {
+    let client = async_client(source)?;
+
+    let sent = Utc::now();
+    let started = Instant::now();
+    let response = client.get(&source.url).send().await?.error_for_status()?;
+    let body = response.json().await?;
+    let rtt = started.elapsed();
+
+    Ok((body, Exchange { sent, rtt }))
+}
+
+/// Async [`head`].
+#[cfg(feature = "async")]
+async fn head_async(source: &SourceConfig) -> Result<(reqwest::header::HeaderMap, Exchange), SourceError> {
+    let client = async_client(source)?;
+
+    let sent = Utc::now();
+    let started = Instant::now();
+    let response = client.head(&source.url).send().await?;
+    let rtt = started.elapsed();
+
+    Ok((response.headers().clone(), Exchange { sent, rtt }))
+}
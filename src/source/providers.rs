@@ -0,0 +1,269 @@
+use std::time::Duration;
+
+use chrono::format::{Fixed, Item, Numeric, StrftimeItems};
+use chrono::{DateTime, LocalResult, NaiveDateTime, Offset, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::{get_json, OffsetMismatch, SourceError, TimeSample, TimeSource};
+#[cfg(feature = "async")]
+use super::{get_json_async, AsyncTimeSource, BoxFuture};
+use crate::config::SourceConfig;
+
+macro_rules! http_json_source {
+    ($ty:ident) => {
+        http_json_source!($ty, Duration::ZERO);
+    };
+    // `$resolution_of` gives the resolution of the timestamp in a body, for
+    // sources whose precision depends on what the server sends.
+    ($ty:ident, resolution_of = $resolution_of:expr) => {
+        http_json_source!(@impl $ty, $resolution_of, |source: &$ty, body: &Value| source.parse(body).map(|time| (time, None)));
+    };
+    ($ty:ident, $resolution:expr) => {
+        http_json_source!($ty, $resolution, |source: &$ty, body: &Value| source.parse(body).map(|time| (time, None)));
+    };
+    // `$read` parses a body into the server's time and any offset it corrected.
+    ($ty:ident, $resolution:expr, $read:expr) => {
+        http_json_source!(@impl $ty, |_: &$ty, _: &Value| $resolution, $read);
+    };
+    (@impl $ty:ident, $resolution_of:expr, $read:expr) => {
+        impl $ty {
+            pub fn new(source: SourceConfig) -> Self {
+                Self { source }
+            }
+        }
+
+        impl TimeSource for $ty {
+            fn name(&self) -> &str {
+                &self.source.url
+            }
+
+            fn weight(&self) -> f64 {
+                self.source.weight
+            }
+
+            fn fetch(&self) -> Result<TimeSample, SourceError> {
+                let (body, exchange) = get_json(&self.source)?;
+                let (server, offset_mismatch) = ($read)(self, &body)?;
+                tracing::trace!(source = %self.source.url, %server, "parsed timestamp");
+                let resolution = ($resolution_of)(self, &body);
+                let sample = TimeSample::from_exchange(exchange.sent, exchange.rtt, server).with_resolution(resolution);
+                Ok(TimeSample { offset_mismatch, ..sample })
+            }
+        }
+
+        #[cfg(feature = "async")]
+        impl AsyncTimeSource for $ty {
+            fn name(&self) -> &str {
+                &self.source.url
+            }
+
+            fn weight(&self) -> f64 {
+                self.source.weight
+            }
+
+            fn fetch(&self) -> BoxFuture<'_, Result<TimeSample, SourceError>> {
+                Box::pin(async move {
+                    let (body, exchange) = get_json_async(&self.source).await?;
+                    let (server, offset_mismatch) = ($read)(self, &body)?;
+                    tracing::trace!(source = %self.source.url, %server, "parsed timestamp");
+                    let resolution = ($resolution_of)(self, &body);
+                    let sample = TimeSample::from_exchange(exchange.sent, exchange.rtt, server).with_resolution(resolution);
+                    Ok(TimeSample { offset_mismatch, ..sample })
+                })
+            }
+        }
+    };
+}
+
+// -- worldtimeapi.org --
+
+/// `{"datetime": "2024-05-01T13:45:12.123456+01:00", "timezone": "Europe/London", ...}`
+///
+/// The offset is checked against the tz database for `timezone`. If they
+/// disagree, the local time is taken as right and re-resolved in the zone,
+/// picking the earlier reading in a repeated hour. A local time the zone
+/// skipped can't be right, so there the instant is kept as sent.
+pub struct WorldTimeApi {
+    source: SourceConfig,
+}
+
+#[derive(Debug, Deserialize)]
+struct WorldTimeApiResponse {
+    datetime: String, // ISO 8601 format
+    timezone: Option<String>,
+}
+
+impl WorldTimeApi {
+    pub fn parse(&self, body: &Value) -> Result<DateTime<Utc>, SourceError> {
+        self.parse_checked(body).map(|(time, _)| time)
+    }
+
+    /// Like [`WorldTimeApi::parse`], also returning the offset correction if one was needed.
+    pub fn parse_checked(&self, body: &Value) -> Result<(DateTime<Utc>, Option<OffsetMismatch>), SourceError> {
+        let json = WorldTimeApiResponse::deserialize(body)?;
+        let parsed = DateTime::parse_from_rfc3339(&json.datetime)?;
+        let Some(zone) = json.timezone.as_deref().and_then(|name| name.parse::<Tz>().ok()) else {
+            return Ok((parsed.with_timezone(&Utc), None));
+        };
+        let reported = *parsed.offset();
+        let local = parsed.naive_local();
+        let (time, expected) = match zone.offset_from_local_datetime(&local) {
+            // In a repeated hour either offset is right.
+            LocalResult::Ambiguous(earlier, later) if [earlier.fix(), later.fix()].contains(&reported) => {
+                return Ok((parsed.with_timezone(&Utc), None));
+            }
+            LocalResult::Single(offset) | LocalResult::Ambiguous(offset, _) => {
+                ((local - offset.fix()).and_utc(), offset.fix())
+            }
+            LocalResult::None => {
+                let time = parsed.with_timezone(&Utc);
+                (time, zone.offset_from_utc_datetime(&time.naive_utc()).fix())
+            }
+        };
+        if expected == reported {
+            return Ok((time, None));
+        }
+
+        let mismatch = OffsetMismatch { zone, reported, expected };
+        tracing::warn!(source = %self.source.url, %mismatch, %time, "provider offset disagrees with the tz database");
+        Ok((time, Some(mismatch)))
+    }
+}
+
+http_json_source!(WorldTimeApi, Duration::ZERO, WorldTimeApi::parse_checked);
+
+// -- timeapi.io --
+
+/// `{"dateTime": "2024-05-01T13:45:12.1234567", "timeZone": "Europe/London", ...}`
+///
+/// The timestamp is local to `timeZone` and carries no offset, so the zone is
+/// resolved through the tz database. Ask for `timeZone=UTC` where possible:
+/// in zones with DST, local times in the repeated hour are ambiguous.
+pub struct TimeApiIo {
+    source: SourceConfig,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TimeApiIoResponse {
+    date_time: String,
+    time_zone: String,
+}
+
+impl TimeApiIo {
+    pub fn parse(&self, body: &Value) -> Result<DateTime<Utc>, SourceError> {
+        let json = TimeApiIoResponse::deserialize(body)?;
+        let local = NaiveDateTime::parse_from_str(&json.date_time, "%Y-%m-%dT%H:%M:%S%.f")?;
+        let zone: Tz = json
+            .time_zone
+            .parse()
+            .map_err(|_| SourceError::Parse(format!("unknown time zone {}", json.time_zone)))?;
+        // A repeated hour at the end of DST is ambiguous; take the earlier reading.
+        let zoned = zone
+            .from_local_datetime(&local)
+            .earliest()
+            .ok_or_else(|| SourceError::Parse(format!("{local} does not exist in {zone}")))?;
+        Ok(zoned.with_timezone(&Utc))
+    }
+}
+
+http_json_source!(TimeApiIo);
+
+// -- worldclockapi.com --
+
+/// `{"currentDateTime": "2024-05-01T13:45Z", ...}` — minute precision only.
+pub struct WorldClockApi {
+    source: SourceConfig,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WorldClockApiResponse {
+    current_date_time: String,
+}
+
+impl WorldClockApi {
+    pub fn parse(&self, body: &Value) -> Result<DateTime<Utc>, SourceError> {
+        let json = WorldClockApiResponse::deserialize(body)?;
+        let text = json.current_date_time;
+        let text = text.strip_suffix('Z').map_or(text.clone(), |t| format!("{t}+00:00"));
+        let parsed = DateTime::parse_from_rfc3339(&text).or_else(|_| DateTime::parse_from_str(&text, "%Y-%m-%dT%H:%M%:z"))?;
+        Ok(parsed.with_timezone(&Utc))
+    }
+}
+
+http_json_source!(WorldClockApi, Duration::from_secs(60));
+
+// -- Generic JSON pointer + format --
+
+/// Reads the value at [`SourceConfig::pointer`] and parses it with [`SourceConfig::format`].
+///
+/// Without a format the value must be RFC 3339. The format `%s` (or a bare
+/// JSON number) is read as Unix seconds, and formats without an offset are
+/// taken to be UTC. Values without a fractional second are taken to be
+/// truncated to whole seconds.
+pub struct JsonPointer {
+    source: SourceConfig,
+}
+
+impl JsonPointer {
+    pub fn parse(&self, body: &Value) -> Result<DateTime<Utc>, SourceError> {
+        let pointer = self.source.pointer.as_deref().unwrap_or_default();
+        let value = body
+            .pointer(pointer)
+            .ok_or_else(|| SourceError::Schema(format!("nothing at {pointer}")))?;
+        let format = self.source.format.as_deref();
+        let out_of_range = || SourceError::Parse(format!("{value} is out of range"));
+
+        if let Some(secs) = value.as_f64() {
+            if format.is_none() || format == Some("%s") {
+                return DateTime::from_timestamp_micros((secs * 1e6).round() as i64).ok_or_else(out_of_range);
+            }
+        }
+
+        let text = value
+            .as_str()
+            .ok_or_else(|| SourceError::Schema(format!("{pointer} is not a string")))?;
+        match format {
+            None => Ok(DateTime::parse_from_rfc3339(text)?.with_timezone(&Utc)),
+            Some("%s") => text
+                .parse::<i64>()
+                .ok()
+                .and_then(|s| DateTime::from_timestamp(s, 0))
+                .ok_or_else(|| SourceError::Parse(format!("{text} is not a Unix timestamp"))),
+            Some(format) => Ok(DateTime::parse_from_str(text, format)
+                .map(|t| t.with_timezone(&Utc))
+                .or_else(|_| NaiveDateTime::parse_from_str(text, format).map(|t| t.and_utc()))?),
+        }
+    }
+
+    /// How finely the value in `body` gives the time: a second, unless it's a
+    /// fractional number or its format has a sub-second field.
+    pub fn resolution(&self, body: &Value) -> Duration {
+        let value = body.pointer(self.source.pointer.as_deref().unwrap_or_default());
+        let subsecond = match (value, self.source.format.as_deref()) {
+            (Some(Value::Number(secs)), None | Some("%s")) => !(secs.is_i64() || secs.is_u64()),
+            (_, Some("%s")) => false,
+            (Some(Value::String(text)), None) => text.contains('.'),
+            (_, Some(format)) => StrftimeItems::new(format).any(|item| {
+                matches!(
+                    item,
+                    Item::Numeric(Numeric::Nanosecond, _)
+                        | Item::Fixed(Fixed::Nanosecond | Fixed::Nanosecond3 | Fixed::Nanosecond6 | Fixed::Nanosecond9)
+                        | Item::Fixed(Fixed::Internal(_))
+                )
+            }),
+            // Nothing there to parse.
+            (_, None) => true,
+        };
+        if subsecond {
+            Duration::ZERO
+        } else {
+            Duration::from_secs(1)
+        }
+    }
+}
+
+http_json_source!(JsonPointer, resolution_of = JsonPointer::resolution);
@@ -0,0 +1,248 @@
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket};
+
+use chrono::{DateTime, Utc};
+
+use super::{SourceError, TimeSample, TimeSource};
+#[cfg(feature = "async")]
+use super::{AsyncTimeSource, BoxFuture};
+use crate::config::SourceConfig;
+
+// -- SNTPv4 (RFC 4330) --
+
+const NTP_PORT: u16 = 123;
+/// Seconds from the NTP epoch (1900) to the Unix epoch (1970).
+const NTP_UNIX_OFFSET: i64 = 2_208_988_800;
+
+const MODE_CLIENT: u8 = 3;
+const MODE_SERVER: u8 = 4;
+const VERSION: u8 = 4;
+const LEAP_UNSYNCHRONIZED: u8 = 3;
+/// How far below zero a round-trip delay may come out from clock resolution
+/// alone before the reply is taken to be lying.
+const DELAY_TOLERANCE: chrono::Duration = chrono::Duration::milliseconds(1);
+
+/// The outcome of one SNTP exchange.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SntpSample {
+    /// Server clock minus local clock: `((T2 - T1) + (T3 - T4)) / 2`.
+    pub offset: chrono::Duration,
+    /// Round-trip network delay: `(T4 - T1) - (T3 - T2)`, never negative.
+    pub delay: chrono::Duration,
+    /// The server's time at the moment the reply arrived (`T4 + offset`).
+    pub time: DateTime<Utc>,
+}
+
+/// An SNTP server, addressed as `host[:port]` or `sntp://host[:port]`.
+pub struct Sntp {
+    source: SourceConfig,
+}
+
+impl Sntp {
+    pub fn new(source: SourceConfig) -> Self {
+        Self { source }
+    }
+
+    /// Sends one client request and checks the reply per RFC 4330 §5.
+    pub fn query(&self) -> Result<SntpSample, SourceError> {
+        let server = self.address()?;
+        let socket = UdpSocket::bind(wildcard(server))?;
+        socket.set_read_timeout(Some(self.source.timeout()))?;
+        socket.connect(server)?;
+
+        let t1 = Utc::now();
+        let request = request(t1);
+        socket.send(&request)?;
+
+        let mut reply = [0u8; 48];
+        let len = socket.recv(&mut reply)?;
+        let t4 = Utc::now();
+        check_reply(&request, &reply[..len], t1, t4)
+    }
+
+    /// Like [`Sntp::query`], without blocking the runtime.
+    #[cfg(feature = "async")]
+    pub async fn query_async(&self) -> Result<SntpSample, SourceError> {
+        let server = self.address_async().await?;
+        let socket = tokio::net::UdpSocket::bind(wildcard(server)).await?;
+        socket.connect(server).await?;
+
+        let t1 = Utc::now();
+        let request = request(t1);
+        socket.send(&request).await?;
+
+        let mut reply = [0u8; 48];
+        let len = tokio::time::timeout(self.source.timeout(), socket.recv(&mut reply))
+            .await
+            .map_err(|_| SourceError::Timeout)??;
+        let t4 = Utc::now();
+        check_reply(&request, &reply[..len], t1, t4)
+    }
+
+    /// `host[:port]`, without the scheme.
+    fn host(&self) -> &str {
+        let url = &self.source.url;
+        url.strip_prefix("sntp://")
+            .or_else(|| url.strip_prefix("ntp://"))
+            .unwrap_or(url)
+            .trim_end_matches('/')
+    }
+
+    fn address(&self) -> Result<SocketAddr, SourceError> {
+        let host = self.host();
+        let unresolved = || SourceError::Dns(format!("{host} has no addresses"));
+        if let Ok(mut addrs) = host.to_socket_addrs() {
+            return addrs.next().ok_or_else(unresolved);
+        }
+        (host, NTP_PORT)
+            .to_socket_addrs()
+            .map_err(|e| SourceError::Dns(format!("{host}: {e}")))?
+            .next()
+            .ok_or_else(unresolved)
+    }
+
+    /// Like [`Sntp::address`], resolving on the runtime rather than blocking it.
+    #[cfg(feature = "async")]
+    async fn address_async(&self) -> Result<SocketAddr, SourceError> {
+        let host = self.host();
+        let unresolved = || SourceError::Dns(format!("{host} has no addresses"));
+        if let Ok(mut addrs) = tokio::net::lookup_host(host).await {
+            return addrs.next().ok_or_else(unresolved);
+        }
+        tokio::net::lookup_host((host, NTP_PORT))
+            .await
+            .map_err(|e| SourceError::Dns(format!("{host}: {e}")))?
+            .next()
+            .ok_or_else(unresolved)
+    }
+}
+
+impl TimeSource for Sntp {
+    fn name(&self) -> &str {
+        &self.source.url
+    }
+
+    fn weight(&self) -> f64 {
+        self.source.weight
+    }
+
+    fn fetch(&self) -> Result<TimeSample, SourceError> {
+        self.query().map(SntpSample::into_time_sample)
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncTimeSource for Sntp {
+    fn name(&self) -> &str {
+        &self.source.url
+    }
+
+    fn weight(&self) -> f64 {
+        self.source.weight
+    }
+
+    fn fetch(&self) -> BoxFuture<'_, Result<TimeSample, SourceError>> {
+        Box::pin(async move { self.query_async().await.map(SntpSample::into_time_sample) })
+    }
+}
+
+impl SntpSample {
+    fn into_time_sample(self) -> TimeSample {
+        TimeSample {
+            local: self.time - self.offset,
+            offset: self.offset,
+            uncertainty: (self.delay / 2).to_std().unwrap_or_default(),
+            offset_mismatch: None,
+        }
+    }
+}
+
+/// The unspecified address of `server`'s family, any port.
+fn wildcard(server: SocketAddr) -> SocketAddr {
+    if server.is_ipv4() {
+        (Ipv4Addr::UNSPECIFIED, 0).into()
+    } else {
+        (Ipv6Addr::UNSPECIFIED, 0).into()
+    }
+}
+
+/// A client request carrying `t1` as its transmit timestamp.
+fn request(t1: DateTime<Utc>) -> [u8; 48] {
+    let mut request = [0u8; 48];
+    request[0] = (VERSION << 3) | MODE_CLIENT;
+    request[40..48].copy_from_slice(&to_ntp(t1));
+    request
+}
+
+/// Validates `reply` to `request` and computes offset and delay from the four timestamps.
+fn check_reply(request: &[u8; 48], reply: &[u8], t1: DateTime<Utc>, t4: DateTime<Utc>) -> Result<SntpSample, SourceError> {
+    let reject = |reason: &str| Err(SourceError::Protocol(reason.to_string()));
+    if reply.len() < 48 {
+        return reject("reply is too short");
+    }
+
+    let leap = reply[0] >> 6;
+    let mode = reply[0] & 0x07;
+    let stratum = reply[1];
+    if leap == LEAP_UNSYNCHRONIZED {
+        return reject("server is unsynchronized");
+    }
+    if mode != MODE_SERVER {
+        return reject("reply is not from a server");
+    }
+    // Stratum 0 is a kiss-o'-death; the server is asking us to go away.
+    if stratum == 0 {
+        return reject("kiss-o'-death");
+    }
+    if stratum > 15 {
+        return reject("stratum out of range");
+    }
+    // The server must echo our transmit timestamp, or this isn't our reply.
+    if reply[24..32] != request[40..48] {
+        return reject("reply does not match our request");
+    }
+
+    let (Some(t2), Some(t3)) = (from_ntp(&reply[32..40]), from_ntp(&reply[40..48])) else {
+        return reject("reply is missing timestamps");
+    };
+    // A server that answers before it hears the question, or claims to have
+    // spent longer on it than the whole round trip took, would otherwise get
+    // a huge interval that overlaps everyone else's.
+    if t3 < t2 {
+        return reject("reply was sent before the request arrived");
+    }
+    let delay = (t4 - t1) - (t3 - t2);
+    if delay < -DELAY_TOLERANCE {
+        return reject("negative round-trip delay");
+    }
+    let delay = delay.max(chrono::Duration::zero());
+    let offset = ((t2 - t1) + (t3 - t4)) / 2;
+    tracing::trace!(stratum, %t2, %t3, delay_us = delay.num_microseconds(), "parsed SNTP reply");
+
+    Ok(SntpSample { offset, delay, time: t4 + offset })
+}
+
+/// Encodes `time` as a 64-bit NTP timestamp. The era is dropped; [`from_ntp`] restores it.
+pub fn to_ntp(time: DateTime<Utc>) -> [u8; 8] {
+    let secs = (time.timestamp() + NTP_UNIX_OFFSET) as u32; // wraps into era 1 from 2036
+    let frac = ((time.timestamp_subsec_nanos() as u64) << 32) / 1_000_000_000;
+    let mut bytes = [0u8; 8];
+    bytes[..4].copy_from_slice(&secs.to_be_bytes());
+    bytes[4..].copy_from_slice(&(frac as u32).to_be_bytes());
+    bytes
+}
+
+/// Decodes a 64-bit NTP timestamp.
+///
+/// Per RFC 4330 §3, values with the top bit clear are taken to be in era 1
+/// (from 2036), which keeps the client working past the era-0 rollover.
+pub fn from_ntp(bytes: &[u8]) -> Option<DateTime<Utc>> {
+    let secs = u32::from_be_bytes(bytes.get(..4)?.try_into().ok()?) as i64;
+    let frac = u32::from_be_bytes(bytes.get(4..8)?.try_into().ok()?) as u64;
+    if secs == 0 && frac == 0 {
+        return None;
+    }
+    let era = if secs & 0x8000_0000 == 0 { 1i64 << 32 } else { 0 };
+    let nanos = (frac * 1_000_000_000) >> 32;
+    DateTime::from_timestamp(secs + era - NTP_UNIX_OFFSET, nanos as u32)
+}
+
@@ -0,0 +1,99 @@
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+
+// -- Last-known-good time --
+
+/// Where the last trusted consensus time is kept between runs, so a host that
+/// boots with a dead RTC never starts out earlier than it has already been —
+/// the same idea as fake-hwclock or systemd-timesyncd's clock file.
+///
+/// The file holds a single RFC 3339 timestamp.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateFile {
+    path: PathBuf,
+}
+
+impl StateFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The stored time, or `None` if nothing has been stored yet.
+    pub fn load(&self) -> io::Result<Option<DateTime<Utc>>> {
+        let text = match fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let time = DateTime::parse_from_rfc3339(text.trim())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Ok(Some(time.with_timezone(&Utc)))
+    }
+
+    /// Replaces the stored time. The new contents are written and synced to a
+    /// temporary file beside the real one, then renamed over it, so a crash
+    /// leaves either the old time or the new one but never a torn file.
+    pub fn store(&self, time: DateTime<Utc>) -> io::Result<()> {
+        let mut temporary = self.path.clone().into_os_string();
+        temporary.push(".tmp");
+        let temporary = PathBuf::from(temporary);
+
+        let mut file = File::create(&temporary)?;
+        writeln!(file, "{}", time.to_rfc3339_opts(SecondsFormat::AutoSi, true))?;
+        file.sync_all()?;
+        drop(file);
+        fs::rename(&temporary, &self.path)?;
+
+        // The rename itself is only durable once the directory is synced.
+        #[cfg(unix)]
+        {
+            let parent = self.path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+            File::open(parent)?.sync_all()?;
+        }
+        Ok(())
+    }
+}
+
+/// The last trusted time: loaded from the state file, then raised by each
+/// agreement and written back.
+#[derive(Debug, Default)]
+pub(crate) struct Floor {
+    state_file: Option<StateFile>,
+    time: Mutex<Option<DateTime<Utc>>>,
+    /// Held from the check to the rename, so concurrent runs can't interleave
+    /// their writes or rename an older time over a newer one.
+    writing: Mutex<()>,
+}
+
+impl Floor {
+    pub(crate) fn new(state_file: Option<StateFile>, time: Option<DateTime<Utc>>) -> Self {
+        Self { state_file, time: Mutex::new(time), writing: Mutex::new(()) }
+    }
+
+    pub(crate) fn get(&self) -> Option<DateTime<Utc>> {
+        *self.time.lock().unwrap()
+    }
+
+    /// Raises the floor to `time` if that's later, storing it in the state
+    /// file first. Blocks on the write.
+    pub(crate) fn raise(&self, time: DateTime<Utc>) {
+        let _writing = self.writing.lock().unwrap();
+        if self.get().is_some_and(|floor| floor >= time) {
+            return;
+        }
+        if let Some(state_file) = &self.state_file {
+            if let Err(error) = state_file.store(time) {
+                tracing::warn!(%error, path = %state_file.path().display(), "cannot write state file");
+            }
+        }
+        *self.time.lock().unwrap() = Some(time);
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// How source trust decays on disagreement and recovers afterwards.
+///
+/// ```toml
+/// [policy.trust]
+/// penalty = 0.5
+/// half_life_secs = 3600
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrustPolicy {
+    /// Factor a source's score is multiplied by each time it disagrees with
+    /// the agreed time, from 0 (distrust at once) to 1 (never penalize).
+    pub penalty: f64,
+    /// Time for half of a lost score to come back.
+    pub half_life_secs: u64,
+}
+
+impl Default for TrustPolicy {
+    fn default() -> Self {
+        Self {
+            penalty: 0.5,
+            half_life_secs: 3_600,
+        }
+    }
+}
+
+impl TrustPolicy {
+    pub fn half_life(&self) -> Duration {
+        Duration::from_secs(self.half_life_secs)
+    }
+}
+
+/// Whether a responding source landed inside the agreed interval.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrustOutcome {
+    Agreed,
+    Disagreed,
+}
+
+/// One entry in a source's trust history.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrustEvent {
+    pub at: DateTime<Utc>,
+    pub outcome: TrustOutcome,
+    /// The score after this event.
+    pub score: f64,
+}
+
+/// A snapshot of one source's trust, for diagnostics.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceTrust {
+    pub name: String,
+    pub score: f64,
+    /// Oldest first, at most [`TrustTracker::HISTORY`] entries.
+    pub history: Vec<TrustEvent>,
+}
+
+#[derive(Debug, Clone)]
+struct TrustState {
+    /// Score as of `updated`; recovery since then is applied on read.
+    score: f64,
+    updated: Instant,
+    history: VecDeque<TrustEvent>,
+}
+
+/// Per-source trust scores in `0.0..=1.0`, multiplied into each source's weight.
+///
+/// Every source starts fully trusted. A source that disagrees with the agreed
+/// time has its score cut by [`TrustPolicy::penalty`]; the lost trust then
+/// comes back exponentially with [`TrustPolicy::half_life`]. A provider that
+/// keeps drifting therefore stays demoted, while a one-off glitch is forgotten.
+#[derive(Debug, Clone, Default)]
+pub struct TrustTracker {
+    policy: TrustPolicy,
+    sources: HashMap<String, TrustState>,
+}
+
+impl TrustTracker {
+    /// How many events each source's history keeps.
+    pub const HISTORY: usize = 32;
+
+    pub fn new(policy: TrustPolicy) -> Self {
+        Self { policy, sources: HashMap::new() }
+    }
+
+    pub fn policy(&self) -> &TrustPolicy {
+        &self.policy
+    }
+
+    /// The score of `name` at `at`; unknown sources are fully trusted.
+    pub fn score_at(&self, name: &str, at: Instant) -> f64 {
+        self.sources.get(name).map_or(1.0, |state| self.recovered(state, at))
+    }
+
+    /// Records whether `name` agreed with a consensus reached at `at` (`when` on the wall clock).
+    pub fn record_at(&mut self, name: &str, outcome: TrustOutcome, at: Instant, when: DateTime<Utc>) {
+        let current = self.score_at(name, at);
+        let score = match outcome {
+            TrustOutcome::Agreed => current,
+            TrustOutcome::Disagreed => current * self.policy.penalty,
+        };
+
+        let state = self.sources.entry(name.to_string()).or_insert_with(|| TrustState {
+            score: 1.0,
+            updated: at,
+            history: VecDeque::with_capacity(Self::HISTORY),
+        });
+        state.score = score;
+        state.updated = at;
+        if state.history.len() == Self::HISTORY {
+            state.history.pop_front();
+        }
+        state.history.push_back(TrustEvent { at: when, outcome, score });
+    }
+
+    /// Every source seen so far, sorted by name, with scores as of `at`.
+    pub fn report_at(&self, at: Instant) -> Vec<SourceTrust> {
+        let mut report: Vec<SourceTrust> = self
+            .sources
+            .iter()
+            .map(|(name, state)| SourceTrust {
+                name: name.clone(),
+                score: self.recovered(state, at),
+                history: state.history.iter().copied().collect(),
+            })
+            .collect();
+        report.sort_by(|a, b| a.name.cmp(&b.name));
+        report
+    }
+
+    fn recovered(&self, state: &TrustState, at: Instant) -> f64 {
+        let half_life = self.policy.half_life().as_secs_f64();
+        if half_life == 0.0 {
+            return 1.0;
+        }
+        let elapsed = at.saturating_duration_since(state.updated).as_secs_f64();
+        1.0 - (1.0 - state.score) * 0.5f64.powf(elapsed / half_life)
+    }
+}
@@ -0,0 +1,145 @@
+#![cfg(feature = "async")]
+
+use std::net::UdpSocket;
+#[cfg(unix)]
+use std::process::Command;
+#[cfg(unix)]
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+#[cfg(unix)]
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, TimeZone, Utc};
+use sybau::{
+    AgreementPath, AsyncTimeConsensus, AsyncTimeSource, Blocking, ClockHandle, ConsensusPolicy, LeapSecondTable,
+    ParserKind, SourceConfig, SourceError, StateFile,
+};
+
+mod common;
+
+use common::{slow_async, sntp_responder, Fake};
+
+fn consensus(sources: Vec<Box<dyn AsyncTimeSource>>) -> AsyncTimeConsensus {
+    let clock = ClockHandle::anchored(Instant::now(), epoch(), LeapSecondTable::default());
+    AsyncTimeConsensus::with_clock(sources, clock).with_not_before(epoch())
+}
+
+fn epoch() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2000, 1, 1, 0, 0, 0).unwrap()
+}
+
+#[tokio::test]
+async fn sources_are_queried_concurrently() {
+    let started = Instant::now();
+    let result = consensus(vec![slow_async("a", 300), slow_async("b", 300), slow_async("c", 300)]).get_date_time().await;
+
+    assert_eq!(result.path, AgreementPath::Unanimous);
+    assert!(started.elapsed() < Duration::from_millis(600), "took {:?}", started.elapsed());
+}
+
+#[tokio::test]
+async fn a_hung_source_is_left_behind_at_the_deadline() {
+    let policy = ConsensusPolicy { deadline_ms: 300, ..ConsensusPolicy::default() };
+    let started = Instant::now();
+    let result = consensus(vec![slow_async("a", 0), slow_async("hung", 10_000), slow_async("c", 50)])
+        .get_date_time_with(&policy)
+        .await;
+
+    assert!(started.elapsed() < Duration::from_secs(1), "took {:?}", started.elapsed());
+    assert_eq!(result.path, AgreementPath::Quorum);
+    assert_eq!(result.sources, ["a", "c"]);
+}
+
+#[tokio::test]
+async fn falls_back_when_nothing_answers() {
+    let policy = ConsensusPolicy { deadline_ms: 100, ..ConsensusPolicy::default() };
+    let result = consensus(vec![slow_async("hung", 10_000)]).get_date_time_with(&policy).await;
+
+    assert!(result.used_fallback);
+    assert_eq!(result.path, AgreementPath::Fallback);
+    assert!((result.time - epoch()).abs() < chrono::Duration::seconds(1));
+}
+
+#[tokio::test]
+async fn blocking_sources_run_off_the_runtime() {
+    // Sleeps on its thread when fetched through `TimeSource`.
+    let sleepy = Fake::offset("sleepy", chrono::Duration::hours(1)).with_delay(Duration::from_millis(50));
+    let sample = AsyncTimeSource::fetch(&Blocking(Arc::new(sleepy))).await.unwrap();
+    assert_eq!(sample.offset, chrono::Duration::hours(1));
+}
+
+#[tokio::test]
+async fn sntp_queries_without_blocking() {
+    let skew = chrono::Duration::seconds(5);
+    let url = sntp_responder(skew, Duration::ZERO, |_| {});
+    let source = SourceConfig::new(url).with_parser(ParserKind::Sntp).build_async();
+    let sample = source.fetch().await.unwrap();
+    assert!((sample.offset - skew).abs() < chrono::Duration::milliseconds(50), "{sample:?}");
+}
+
+#[tokio::test]
+async fn sntp_gives_up_after_the_timeout() {
+    // Bound but never answered.
+    let silent = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let mut config = SourceConfig::new(format!("sntp://{}", silent.local_addr().unwrap())).with_parser(ParserKind::Sntp);
+    config.timeout_ms = 100;
+
+    let started = Instant::now();
+    assert_eq!(config.build_async().fetch().await, Err(SourceError::Timeout));
+    assert!(started.elapsed() < Duration::from_secs(1));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn sntp_resolves_names_on_the_runtime() {
+    let config = SourceConfig::new("sntp://time.invalid").with_parser(ParserKind::Sntp);
+    assert!(matches!(config.build_async().fetch().await, Err(SourceError::Dns(_))));
+}
+
+#[tokio::test]
+async fn agreed_times_are_persisted() {
+    let path = std::env::temp_dir().join(format!("sybau-{}-async-state", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let consensus = consensus(vec![slow_async("a", 0), slow_async("b", 0)]).with_state_file(&path);
+
+    let result = consensus.get_date_time().await;
+    assert!(!result.used_fallback);
+    assert_eq!(StateFile::new(&path).load().unwrap(), Some(result.time));
+    std::fs::remove_file(path).unwrap();
+}
+
+#[cfg(unix)]
+#[tokio::test(flavor = "current_thread")]
+async fn state_file_writes_leave_the_runtime_free() {
+    let path = std::env::temp_dir().join(format!("sybau-{}-async-blocked-state", std::process::id()));
+    let temporary = path.with_extension("tmp");
+    let _ = std::fs::remove_file(&path);
+    let _ = std::fs::remove_file(&temporary);
+    // Opening a FIFO to write blocks until someone opens it to read, so the
+    // store hangs until this test reads it.
+    assert!(Command::new("mkfifo").arg(&temporary).status().unwrap().success());
+
+    // If the store blocks the only runtime thread, the test can't read the
+    // FIFO. Read it from a thread so the test fails instead of hanging.
+    let rescued = Arc::new(AtomicBool::new(false));
+    let watchdog = (Arc::clone(&rescued), temporary.clone());
+    thread::spawn(move || {
+        thread::sleep(Duration::from_secs(5));
+        if std::fs::metadata(&watchdog.1).is_ok() {
+            watchdog.0.store(true, Ordering::SeqCst);
+            let _ = std::fs::read(&watchdog.1);
+        }
+    });
+
+    let consensus = consensus(vec![slow_async("a", 0), slow_async("b", 0)]).with_state_file(&path);
+    let run = tokio::spawn(async move { consensus.get_date_time().await });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(!rescued.load(Ordering::SeqCst), "the state file write blocked the runtime");
+    assert!(!run.is_finished());
+
+    let written = tokio::task::spawn_blocking(move || std::fs::read_to_string(temporary)).await.unwrap().unwrap();
+    let result = run.await.unwrap();
+    assert_eq!(DateTime::parse_from_rfc3339(written.trim()).unwrap(), result.time);
+    let _ = std::fs::remove_file(&path);
+    let _ = std::fs::remove_file(path.with_extension("tmp"));
+}
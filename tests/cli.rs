@@ -0,0 +1,139 @@
+use std::net::TcpListener;
+use std::process::{Command, Output};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+mod common;
+
+use common::{http_response, http_server};
+
+/// A loopback worldtimeapi.org look-alike that answers `requests` times with `datetime`.
+fn server(datetime: &'static str, requests: usize) -> String {
+    let body = format!(r#"{{"datetime": "{datetime}"}}"#);
+    http_server(http_response("200 OK", "Content-Type: application/json\r\n", &body), requests, Duration::ZERO)
+}
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_sybau")).args(args).output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+const FAR_FUTURE: &str = "2040-02-29T12:00:00.500+00:00";
+
+fn far_future() -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(FAR_FUTURE).unwrap().with_timezone(&Utc)
+}
+
+#[test]
+fn rfc3339_output_parses_back() {
+    let output = run(&["--source", &server(FAR_FUTURE, 1), "--format", "rfc3339"]);
+    let printed = DateTime::parse_from_rfc3339(stdout(&output).trim()).unwrap();
+    assert!((printed.with_timezone(&Utc) - far_future()).abs() < chrono::Duration::seconds(1));
+}
+
+#[test]
+fn unix_output_is_seconds_since_the_epoch() {
+    let output = run(&["--source", &server(FAR_FUTURE, 1), "--format", "unix"]);
+    let printed: f64 = stdout(&output).trim().parse().unwrap();
+    assert!((printed - far_future().timestamp() as f64).abs() < 1.5);
+}
+
+#[test]
+fn custom_output_uses_the_strftime_format() {
+    let output = run(&["--source", &server(FAR_FUTURE, 1), "--format", "custom=%Y/%j"]);
+    assert_eq!(stdout(&output).trim(), "2040/060");
+}
+
+#[test]
+fn invalid_formats_are_rejected() {
+    assert!(!run(&["--format", "xml"]).status.success());
+    assert!(!run(&["--format", "custom=%Q"]).status.success());
+}
+
+#[test]
+fn json_output_lists_every_source() {
+    let agreeing = server(FAR_FUTURE, 1);
+    let also_agreeing = server(FAR_FUTURE, 1);
+    let refused = format!("http://127.0.0.1:{}/", TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port());
+    let output = run(&["--source", &agreeing, "--source", &also_agreeing, "--source", &refused, "--format", "json"]);
+    let json: Value = serde_json::from_str(&stdout(&output)).unwrap();
+
+    assert_eq!(json["path"], "quorum");
+    assert_eq!(json["used_fallback"], false);
+    let time = DateTime::parse_from_rfc3339(json["time"].as_str().unwrap()).unwrap();
+    assert!((time.with_timezone(&Utc) - far_future()).abs() < chrono::Duration::seconds(1));
+
+    let sources = json["sources"].as_array().unwrap();
+    assert_eq!(sources.len(), 3);
+    assert_eq!(sources[0]["name"], agreeing.as_str());
+    assert_eq!(sources[0]["status"], "agreed");
+    assert!(sources[0]["offset_ms"].as_f64().unwrap() > 0.0);
+    assert_eq!(sources[2]["status"], "failed");
+    assert!(sources[2]["error"].as_str().unwrap().starts_with("cannot connect"));
+}
+
+#[test]
+fn times_are_shown_in_the_requested_zone() {
+    let output = run(&["--source", &server(FAR_FUTURE, 1), "--tz", "America/New_York", "--format", "rfc3339"]);
+    let printed = DateTime::parse_from_rfc3339(stdout(&output).trim()).unwrap();
+    assert_eq!(printed.offset().local_minus_utc(), -5 * 3600);
+    assert!((printed.with_timezone(&Utc) - far_future()).abs() < chrono::Duration::seconds(1));
+
+    let output = run(&["--source", &server(FAR_FUTURE, 1), "--tz", "America/New_York"]);
+    assert!(stdout(&output).contains("07:00:00 EST"));
+}
+
+#[test]
+fn unknown_zones_are_rejected() {
+    assert!(!run(&["--tz", "Mars/Olympus_Mons"]).status.success());
+}
+
+#[test]
+fn source_options_set_the_per_source_fields() {
+    let source = format!("{},parser=world-time-api,weight=2,timeout_ms=2000", server(FAR_FUTURE, 1));
+    let output = run(&["--source", &source, "--format", "custom=%Y"]);
+    assert_eq!(stdout(&output).trim(), "2040");
+
+    assert!(!run(&["--source", "http://127.0.0.1:1/,parser=carrier-pigeon"]).status.success());
+    assert!(!run(&["--source", "http://127.0.0.1:1/,colour=red"]).status.success());
+    assert!(!run(&["--source", "http://127.0.0.1:1/,weight=0"]).status.success());
+}
+
+#[test]
+fn source_urls_and_options_may_contain_commas() {
+    let source = format!("{}?fields=date,time,parser=world-time-api", server(FAR_FUTURE, 1));
+    assert_eq!(stdout(&run(&["--source", &source, "--format", "custom=%Y"])).trim(), "2040");
+
+    let url = server("Wed, 29 Feb 2040 12:00:00 +0000", 1);
+    let source = format!("{url},parser=json-pointer,pointer=/datetime,format=%a, %d %b %Y %H:%M:%S %z");
+    assert_eq!(stdout(&run(&["--source", &source, "--format", "custom=%F"])).trim(), "2040-02-29");
+
+    // A comma followed by `word=` always starts an option.
+    assert!(!run(&["--source", "http://127.0.0.1:1/?a=1,b=2"]).status.success());
+}
+
+#[test]
+fn duplicate_sources_are_rejected() {
+    let output = run(&["--source", "http://127.0.0.1:1/", "--source", "http://127.0.0.1:1/,weight=2"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("listed more than once"));
+}
+
+#[test]
+fn sources_from_flags_keep_the_rest_of_the_config() {
+    let path = std::env::temp_dir().join(format!("sybau-{}-cli-config.toml", std::process::id()));
+    std::fs::write(&path, "[[sources]]\nurl = \"https://a.example\"\n[[sources]]\nurl = \"https://b.example\"\n[policy]\nmin_quorum = 2\n")
+        .unwrap();
+
+    // The config's quorum of two can't be met by the one source given.
+    let output = run(&["--config", path.to_str().unwrap(), "--source", "http://127.0.0.1:1/"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("quorum of 2"));
+    std::fs::remove_file(path).unwrap();
+}
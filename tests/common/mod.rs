@@ -0,0 +1,218 @@
+//! Fixtures shared by the integration tests. Each test crate uses a subset.
+#![allow(dead_code)]
+
+use std::io::{Read, Write};
+use std::net::{TcpListener, UdpSocket};
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use chrono::{DateTime, TimeZone, Utc};
+use sybau::{to_ntp, SourceError, TimeSample, TimeSource};
+#[cfg(feature = "async")]
+use sybau::{AsyncTimeSource, BoxFuture};
+
+pub fn utc(y: i32, mo: u32, d: u32, h: u32, mi: u32, s: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(y, mo, d, h, mi, s).unwrap()
+}
+
+/// A reading taken now, `offset` from the local clock.
+pub fn sample(offset: chrono::Duration, uncertainty: Duration) -> TimeSample {
+    TimeSample { local: Utc::now(), offset, uncertainty, offset_mismatch: None }
+}
+
+// -- Fake sources --
+
+/// What a [`Fake`] source reports.
+pub enum Report {
+    /// This instant, whenever it is asked.
+    Time(DateTime<Utc>),
+    /// The local time plus this many milliseconds, which can change between runs.
+    OffsetMs(Arc<AtomicI64>),
+}
+
+/// A configurable source. It answers after `delay` (asleep on its thread when
+/// fetched blocking, on the tokio timer when fetched async) and times out
+/// while `up` is false.
+pub struct Fake {
+    pub name: String,
+    pub weight: f64,
+    pub report: Report,
+    pub uncertainty: Duration,
+    pub delay: Duration,
+    pub up: Arc<AtomicBool>,
+}
+
+impl Fake {
+    pub fn new(name: &str, report: Report) -> Self {
+        Self {
+            name: name.to_string(),
+            weight: 1.0,
+            report,
+            uncertainty: Duration::from_millis(100),
+            delay: Duration::ZERO,
+            up: Arc::new(AtomicBool::new(true)),
+        }
+    }
+
+    /// Reports `time`.
+    pub fn at(name: &str, time: DateTime<Utc>) -> Self {
+        Self::new(name, Report::Time(time))
+    }
+
+    /// Reports the local time plus `offset`.
+    pub fn offset(name: &str, offset: chrono::Duration) -> Self {
+        Self::new(name, Report::OffsetMs(Arc::new(AtomicI64::new(offset.num_milliseconds()))))
+    }
+
+    pub fn with_weight(self, weight: f64) -> Self {
+        Self { weight, ..self }
+    }
+
+    pub fn with_uncertainty(self, uncertainty: Duration) -> Self {
+        Self { uncertainty, ..self }
+    }
+
+    pub fn with_delay(self, delay: Duration) -> Self {
+        Self { delay, ..self }
+    }
+
+    /// Answers only while `up` is set.
+    pub fn with_switch(self, up: &Arc<AtomicBool>) -> Self {
+        Self { up: Arc::clone(up), ..self }
+    }
+
+    /// Times out whenever it is asked.
+    pub fn down(self) -> Self {
+        self.up.store(false, Ordering::SeqCst);
+        self
+    }
+
+    pub fn boxed(self) -> Box<dyn TimeSource> {
+        Box::new(self)
+    }
+
+    #[cfg(feature = "async")]
+    pub fn boxed_async(self) -> Box<dyn AsyncTimeSource> {
+        Box::new(self)
+    }
+
+    fn answer(&self) -> Result<TimeSample, SourceError> {
+        if !self.up.load(Ordering::SeqCst) {
+            return Err(SourceError::Timeout);
+        }
+        let answer = sample(chrono::Duration::zero(), self.uncertainty);
+        let offset = match &self.report {
+            Report::Time(time) => *time - answer.local,
+            Report::OffsetMs(ms) => chrono::Duration::milliseconds(ms.load(Ordering::SeqCst)),
+        };
+        Ok(TimeSample { offset, ..answer })
+    }
+}
+
+impl TimeSource for Fake {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn weight(&self) -> f64 {
+        self.weight
+    }
+
+    fn fetch(&self) -> Result<TimeSample, SourceError> {
+        thread::sleep(self.delay);
+        self.answer()
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncTimeSource for Fake {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn weight(&self) -> f64 {
+        self.weight
+    }
+
+    fn fetch(&self) -> BoxFuture<'_, Result<TimeSample, SourceError>> {
+        Box::pin(async move {
+            tokio::time::sleep(self.delay).await;
+            self.answer()
+        })
+    }
+}
+
+pub fn source(name: &str, time: DateTime<Utc>) -> Box<dyn TimeSource> {
+    Fake::at(name, time).boxed()
+}
+
+pub fn sources(times: &[(&str, DateTime<Utc>)]) -> Vec<Box<dyn TimeSource>> {
+    times.iter().map(|&(name, time)| source(name, time)).collect()
+}
+
+/// A correct source that takes `millis` to answer.
+fn slow_fake(name: &str, millis: u64) -> Fake {
+    Fake::offset(name, chrono::Duration::zero())
+        .with_uncertainty(Duration::from_millis(10))
+        .with_delay(Duration::from_millis(millis))
+}
+
+pub fn slow(name: &str, millis: u64) -> Box<dyn TimeSource> {
+    slow_fake(name, millis).boxed()
+}
+
+#[cfg(feature = "async")]
+pub fn slow_async(name: &str, millis: u64) -> Box<dyn AsyncTimeSource> {
+    slow_fake(name, millis).boxed_async()
+}
+
+// -- Loopback servers --
+
+/// An HTTP/1.1 response with `status`, any extra `headers` (each ending in
+/// `\r\n`) and `body`.
+pub fn http_response(status: &str, headers: &str, body: &str) -> String {
+    format!("HTTP/1.1 {status}\r\n{headers}Content-Length: {}\r\nConnection: close\r\n\r\n{body}", body.len())
+}
+
+/// A loopback HTTP server that answers `requests` requests, one per
+/// connection, by sending `response` verbatim after `hold`.
+pub fn http_server(response: String, requests: usize, hold: Duration) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    thread::spawn(move || {
+        for _ in 0..requests {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0u8; 1024];
+            let _ = stream.read(&mut request).unwrap();
+            thread::sleep(hold);
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+    format!("http://{addr}/")
+}
+
+/// A one-shot loopback SNTP server whose clock runs `skew` ahead of ours and
+/// which spends `hold` between receiving the request and replying. `tweak`
+/// can spoil the reply before it is sent.
+pub fn sntp_responder(skew: chrono::Duration, hold: Duration, tweak: fn(&mut [u8; 48])) -> String {
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let addr = socket.local_addr().unwrap();
+    thread::spawn(move || {
+        let mut request = [0u8; 48];
+        let (_, peer) = socket.recv_from(&mut request).unwrap();
+        let received = Utc::now() + skew;
+        thread::sleep(hold);
+
+        let mut reply = [0u8; 48];
+        reply[0] = (4 << 3) | 4; // no leap warning, v4, server
+        reply[1] = 2; // stratum
+        reply[24..32].copy_from_slice(&request[40..48]);
+        reply[32..40].copy_from_slice(&to_ntp(received));
+        reply[40..48].copy_from_slice(&to_ntp(Utc::now() + skew));
+        tweak(&mut reply);
+        socket.send_to(&reply, peer).unwrap();
+    });
+    format!("sntp://{addr}")
+}
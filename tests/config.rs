@@ -0,0 +1,178 @@
+use std::time::Duration;
+
+use sybau::{Config, ConfigError, ConsensusPolicy, ParserKind, ResyncPolicy, Selection, SourceConfig};
+
+#[test]
+fn toml_sources_fill_in_defaults() {
+    let config = Config::from_toml(
+        r#"
+        [[sources]]
+        url = "https://time.internal.example/now"
+        weight = 2.5
+        timeout_ms = 1500
+
+        [[sources]]
+        url = "https://backup.internal.example/now"
+        enabled = false
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(config.sources.len(), 2);
+    let first = &config.sources[0];
+    assert_eq!(first.parser, ParserKind::WorldTimeApi);
+    assert_eq!(first.weight, 2.5);
+    assert_eq!(first.timeout(), Duration::from_millis(1500));
+    assert!(first.enabled);
+
+    let enabled = config.enabled_sources();
+    assert_eq!(enabled, vec![first.clone()]);
+}
+
+#[test]
+fn json_and_toml_describe_the_same_config() {
+    let json = Config::from_json(
+        r#"{"sources": [{"url": "https://a.example", "parser": "world-time-api", "weight": 3}]}"#,
+    )
+    .unwrap();
+    let toml = Config::from_toml(
+        "[[sources]]\nurl = \"https://a.example\"\nparser = \"world-time-api\"\nweight = 3.0\n",
+    )
+    .unwrap();
+    assert_eq!(json, toml);
+}
+
+#[test]
+fn a_single_source_is_enough() {
+    let config = Config::from_toml("[[sources]]\nurl = \"https://only.example\"\n").unwrap();
+    assert_eq!(config.enabled_sources(), vec![SourceConfig::new("https://only.example")]);
+}
+
+#[test]
+fn rejects_configs_without_enabled_sources() {
+    assert!(matches!(Config::from_toml("sources = []"), Err(ConfigError::NoSources)));
+    assert!(matches!(
+        Config::from_toml("[[sources]]\nurl = \"https://a.example\"\nenabled = false\n"),
+        Err(ConfigError::NoSources)
+    ));
+}
+
+#[test]
+fn rejects_non_positive_weights() {
+    let err = Config::from_toml("[[sources]]\nurl = \"https://a.example\"\nweight = 0.0\n").unwrap_err();
+    assert!(matches!(err, ConfigError::InvalidWeight(url) if url == "https://a.example"));
+}
+
+#[test]
+fn rejects_trust_penalties_outside_zero_to_one() {
+    for penalty in ["-3.0", "1.5", "nan"] {
+        let text = format!("[[sources]]\nurl = \"https://a.example\"\n[policy.trust]\npenalty = {penalty}\n");
+        assert!(matches!(Config::from_toml(&text), Err(ConfigError::InvalidPenalty(_))), "{penalty}");
+    }
+    for penalty in ["0.0", "1.0"] {
+        let text = format!("[[sources]]\nurl = \"https://a.example\"\n[policy.trust]\npenalty = {penalty}\n");
+        assert!(Config::from_toml(&text).is_ok(), "{penalty}");
+    }
+}
+
+#[test]
+fn rejects_unknown_parsers() {
+    let err = Config::from_toml("[[sources]]\nurl = \"https://a.example\"\nparser = \"mystery\"\n").unwrap_err();
+    assert!(matches!(err, ConfigError::Parse(_)));
+}
+
+#[test]
+fn default_config_lists_the_builtin_providers() {
+    assert_eq!(Config::default().enabled_sources().len(), 3);
+}
+
+#[test]
+fn json_pointer_sources_need_a_pointer() {
+    let err = Config::from_toml("[[sources]]\nurl = \"https://a.example\"\nparser = \"json-pointer\"\n").unwrap_err();
+    assert!(matches!(err, ConfigError::MissingPointer(_)));
+
+    let config = Config::from_toml(
+        "[[sources]]\nurl = \"https://a.example\"\nparser = \"json-pointer\"\npointer = \"/now\"\nformat = \"%s\"\n",
+    )
+    .unwrap();
+    assert_eq!(config.sources[0].pointer.as_deref(), Some("/now"));
+    assert_eq!(config.sources[0].format.as_deref(), Some("%s"));
+}
+
+#[test]
+fn every_builtin_provider_has_its_own_parser() {
+    let parsers: Vec<_> = Config::default().sources.iter().map(|s| s.parser).collect();
+    assert_eq!(parsers, [ParserKind::WorldTimeApi, ParserKind::TimeApiIo, ParserKind::WorldClockApi]);
+}
+
+#[test]
+fn policy_section_is_optional_and_parsed_when_present() {
+    let bare = Config::from_toml("[[sources]]\nurl = \"https://a.example\"\n").unwrap();
+    assert_eq!(bare.policy, ConsensusPolicy::default());
+
+    let sources: String = (1..=5).map(|n| format!("[[sources]]\nurl = \"https://{n}.example\"\n")).collect();
+    let config = Config::from_toml(&format!(
+        "{sources}[policy]\nmin_quorum = 3\nmax_spread_secs = 2\nselection = \"median\"\n"
+    ))
+    .unwrap();
+    assert_eq!(config.policy.min_quorum, Some(3));
+    assert_eq!(config.policy.max_spread(), Some(Duration::from_secs(2)));
+    assert_eq!(config.policy.selection, Selection::Median);
+    assert_eq!(config.policy.tolerance(), ConsensusPolicy::default().tolerance());
+}
+
+#[test]
+fn default_quorum_is_a_strict_majority() {
+    let policy = ConsensusPolicy::default();
+    assert_eq!(policy.quorum(1), 1);
+    assert_eq!(policy.quorum(2), 2);
+    assert_eq!(policy.quorum(3), 2);
+    assert_eq!(policy.quorum(5), 3);
+}
+
+#[test]
+fn rejects_unreachable_quorums() {
+    let err = Config::from_toml("[[sources]]\nurl = \"https://a.example\"\n[policy]\nmin_quorum = 2\n").unwrap_err();
+    assert!(matches!(err, ConfigError::UnreachableQuorum { quorum: 2, enabled: 1 }));
+
+    let err = Config::from_toml("[[sources]]\nurl = \"https://a.example\"\n[policy]\nmin_quorum = 0\n").unwrap_err();
+    assert!(matches!(err, ConfigError::UnreachableQuorum { quorum: 0, .. }));
+}
+
+#[test]
+fn resync_section_is_optional_and_parsed_when_present() {
+    let bare = Config::from_toml("[[sources]]\nurl = \"https://a.example\"\n").unwrap();
+    assert_eq!(bare.resync, ResyncPolicy::default());
+
+    let config =
+        Config::from_toml("[[sources]]\nurl = \"https://a.example\"\n[resync]\ninterval_secs = 60\njitter_ms = 0\n").unwrap();
+    assert_eq!(config.resync.interval(), Duration::from_secs(60));
+    assert_eq!(config.resync.jitter(), Duration::ZERO);
+    assert_eq!(config.resync.retry_secs, ResyncPolicy::default().retry_secs);
+
+    let err = Config::from_toml("[[sources]]\nurl = \"https://a.example\"\n[resync]\ninterval_secs = 0\n").unwrap_err();
+    assert!(matches!(err, ConfigError::ZeroInterval));
+}
+
+#[test]
+fn rejects_duplicate_sources() {
+    let text = "[[sources]]\nurl = \"https://a.example\"\n[[sources]]\nurl = \"https://a.example\"\nparser = \"json-pointer\"\npointer = \"/now\"\n";
+    assert!(matches!(Config::from_toml(text), Err(ConfigError::DuplicateSource(url)) if url == "https://a.example"));
+}
+
+#[test]
+fn rejects_zero_backoff() {
+    for field in ["retry_secs", "max_backoff_secs"] {
+        let text = format!("[[sources]]\nurl = \"https://a.example\"\n[resync]\n{field} = 0\njitter_ms = 0\n");
+        assert!(matches!(Config::from_toml(&text), Err(ConfigError::ZeroBackoff)), "{field}");
+    }
+}
+
+#[test]
+fn the_horizon_must_be_positive() {
+    let config = Config::from_toml("[[sources]]\nurl = \"https://a.example\"\n[policy]\nhorizon_days = 3650\n").unwrap();
+    assert_eq!(config.policy.horizon_days, 3650);
+
+    let err = Config::from_toml("[[sources]]\nurl = \"https://a.example\"\n[policy]\nhorizon_days = 0\n").unwrap_err();
+    assert!(matches!(err, ConfigError::ZeroHorizon));
+}
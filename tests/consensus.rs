@@ -0,0 +1,446 @@
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use sybau::{
+    build_time, AgreementPath, ClockHandle, ConsensusPolicy, LeapSecondTable, Selection, SourceError, TimeConsensus, TimeSource,
+};
+
+mod common;
+
+use common::{slow, source, utc, Fake};
+
+/// The fixtures are dated 2026 on, which may predate the build.
+fn consensus(sources: Vec<Box<dyn TimeSource>>) -> TimeConsensus {
+    let clock = ClockHandle::anchored(Instant::now(), utc(2000, 1, 1, 0, 0, 0), LeapSecondTable::default());
+    TimeConsensus::with_clock(sources, clock).with_not_before(utc(2000, 1, 1, 0, 0, 0))
+}
+
+fn close(a: DateTime<Utc>, b: DateTime<Utc>) -> bool {
+    (a - b).abs() < chrono::Duration::milliseconds(200)
+}
+
+#[test]
+fn agreeing_sources_are_unanimous() {
+    let t = utc(2026, 6, 1, 12, 0, 0);
+    let result = consensus(vec![
+        source("a", t),
+        source("b", t + chrono::Duration::milliseconds(300)),
+        source("c", t - chrono::Duration::milliseconds(300)),
+    ])
+    .get_date_time();
+
+    assert_eq!(result.path, AgreementPath::Unanimous);
+    assert!(!result.used_fallback);
+    assert_eq!(result.sources, ["a", "b", "c"]);
+    assert!(close(result.time, t));
+    let interval = result.interval.unwrap();
+    assert!(interval.contains(result.time));
+    assert!(result.error_bound.unwrap() <= Duration::from_secs(2));
+}
+
+#[test]
+fn a_falseticker_is_outvoted() {
+    let t = utc(2026, 6, 1, 12, 0, 0);
+    let result = consensus(vec![
+        source("a", t),
+        source("liar", t + chrono::Duration::minutes(7)),
+        source("c", t + chrono::Duration::milliseconds(500)),
+    ])
+    .get_date_time();
+
+    assert_eq!(result.path, AgreementPath::Quorum);
+    assert_eq!(result.sources, ["a", "c"]);
+    assert!(close(result.time, t + chrono::Duration::milliseconds(250)));
+}
+
+#[test]
+fn minutes_59_and_01_are_two_minutes_apart_not_58() {
+    let t = utc(2026, 6, 1, 12, 59, 59);
+    let result = consensus(vec![
+        source("a", t),
+        source("b", t + chrono::Duration::seconds(1)),
+        source("c", t + chrono::Duration::milliseconds(500)),
+    ])
+    .get_date_time();
+
+    assert_eq!(result.path, AgreementPath::Unanimous);
+    assert!(close(result.time, t + chrono::Duration::milliseconds(500)));
+}
+
+#[test]
+fn no_majority_falls_back() {
+    let t = utc(2026, 6, 1, 12, 0, 0);
+    let result = consensus(vec![
+        source("a", t),
+        source("b", t + chrono::Duration::minutes(5)),
+        source("c", t + chrono::Duration::minutes(10)),
+    ])
+    .get_date_time();
+
+    assert_eq!(result.path, AgreementPath::Fallback);
+    assert!(result.used_fallback);
+    assert!(result.interval.is_none());
+}
+
+#[test]
+fn uncertain_sources_can_still_overlap() {
+    let t = utc(2026, 6, 1, 12, 0, 0);
+    let wide = |name, time| Fake::at(name, time).with_uncertainty(Duration::from_secs(30)).boxed();
+    let result = consensus(vec![wide("a", t), wide("b", t + chrono::Duration::seconds(45))]).get_date_time();
+
+    assert_eq!(result.path, AgreementPath::Unanimous);
+    let interval = result.interval.unwrap();
+    assert!(close(interval.earliest, t + chrono::Duration::seconds(14)));
+    assert!(close(interval.latest, t + chrono::Duration::seconds(31)));
+}
+
+/// Three sources straddling `boundary`: two just before it, one just after.
+fn straddle(boundary: DateTime<Utc>) -> TimeConsensus {
+    consensus(vec![
+        source("before", boundary - chrono::Duration::milliseconds(700)),
+        source("after", boundary + chrono::Duration::milliseconds(400)),
+        source("edge", boundary - chrono::Duration::milliseconds(100)),
+    ])
+}
+
+fn assert_agrees_across(boundary: DateTime<Utc>) {
+    let result = straddle(boundary).get_date_time();
+    assert_eq!(result.path, AgreementPath::Unanimous, "boundary {boundary}");
+    // The weighted mean of -700, +400 and -100 ms.
+    let expected = boundary - chrono::Duration::milliseconds(133);
+    assert!(close(result.time, expected), "boundary {boundary}: got {}", result.time);
+}
+
+#[test]
+fn agrees_across_a_minute_boundary() {
+    assert_agrees_across(utc(2026, 6, 1, 12, 1, 0));
+}
+
+#[test]
+fn agrees_across_an_hour_boundary() {
+    assert_agrees_across(utc(2026, 6, 1, 13, 0, 0));
+}
+
+#[test]
+fn agrees_across_a_day_boundary() {
+    assert_agrees_across(utc(2026, 6, 2, 0, 0, 0));
+}
+
+#[test]
+fn agrees_across_a_month_boundary() {
+    assert_agrees_across(utc(2026, 5, 1, 0, 0, 0));
+}
+
+#[test]
+fn agrees_across_a_year_boundary() {
+    assert_agrees_across(utc(2027, 1, 1, 0, 0, 0));
+}
+
+#[test]
+fn agrees_across_a_leap_day() {
+    assert_agrees_across(utc(2028, 2, 29, 0, 0, 0));
+    assert_agrees_across(utc(2028, 3, 1, 0, 0, 0));
+}
+
+#[test]
+fn year_boundary_result_is_not_stitched_from_different_sources() {
+    // One source still in 2026, one already in 2027; the answer must be a real
+    // instant between them, not 2026's date with 2027's hour.
+    let boundary = utc(2027, 1, 1, 0, 0, 0);
+    let result = consensus(vec![
+        source("old-year", boundary - chrono::Duration::milliseconds(500)),
+        source("new-year", boundary + chrono::Duration::milliseconds(500)),
+    ])
+    .get_date_time();
+
+    assert_eq!(result.path, AgreementPath::Unanimous);
+    assert!(close(result.time, boundary));
+    let interval = result.interval.unwrap();
+    assert!(interval.earliest < boundary && boundary < interval.latest);
+}
+
+#[test]
+fn an_hour_apart_is_disagreement_even_with_equal_minutes() {
+    let t = utc(2026, 6, 1, 12, 30, 0);
+    let result = consensus(vec![
+        source("a", t),
+        source("b", t + chrono::Duration::hours(1)),
+    ])
+    .get_date_time();
+
+    assert_eq!(result.path, AgreementPath::Fallback);
+}
+
+fn five_with_two_liars(t: DateTime<Utc>) -> TimeConsensus {
+    consensus(vec![
+        source("a", t),
+        source("b", t + chrono::Duration::milliseconds(200)),
+        source("c", t + chrono::Duration::milliseconds(400)),
+        source("liar-1", t + chrono::Duration::minutes(3)),
+        source("liar-2", t - chrono::Duration::minutes(9)),
+    ])
+}
+
+#[test]
+fn three_of_five_quorum_is_met_by_three() {
+    let t = utc(2026, 6, 1, 12, 0, 0);
+    let policy = ConsensusPolicy { min_quorum: Some(3), ..ConsensusPolicy::default() };
+    let result = five_with_two_liars(t).get_date_time_with(&policy);
+
+    assert_eq!(result.path, AgreementPath::Quorum);
+    assert_eq!(result.sources, ["a", "b", "c"]);
+}
+
+#[test]
+fn four_of_five_quorum_is_not_met_by_three() {
+    let t = utc(2026, 6, 1, 12, 0, 0);
+    let policy = ConsensusPolicy { min_quorum: Some(4), ..ConsensusPolicy::default() };
+    let result = five_with_two_liars(t).get_date_time_with(&policy);
+
+    assert_eq!(result.path, AgreementPath::Fallback);
+}
+
+#[test]
+fn max_spread_rejects_loosely_agreeing_sources() {
+    let t = utc(2026, 6, 1, 12, 0, 0);
+    let sources = || {
+        vec![
+            source("a", t),
+            source("b", t + chrono::Duration::seconds(2)),
+            source("c", t + chrono::Duration::seconds(4)),
+        ]
+    };
+    // A wide tolerance lets all three overlap.
+    let loose = ConsensusPolicy { tolerance_ms: 3_000, ..ConsensusPolicy::default() };
+    assert_eq!(consensus(sources()).get_date_time_with(&loose).path, AgreementPath::Unanimous);
+
+    let strict = ConsensusPolicy { max_spread_secs: Some(3), ..loose };
+    assert_eq!(consensus(sources()).get_date_time_with(&strict).path, AgreementPath::Fallback);
+}
+
+#[test]
+fn tolerance_controls_how_far_apart_sources_may_be() {
+    let t = utc(2026, 6, 1, 12, 0, 0);
+    let sources = || vec![source("a", t), source("b", t + chrono::Duration::seconds(5))];
+
+    let tight = ConsensusPolicy::default();
+    assert_eq!(consensus(sources()).get_date_time_with(&tight).path, AgreementPath::Fallback);
+
+    let wide = ConsensusPolicy { tolerance_ms: 3_000, ..ConsensusPolicy::default() };
+    assert_eq!(consensus(sources()).get_date_time_with(&wide).path, AgreementPath::Unanimous);
+}
+
+#[test]
+fn median_selection_ignores_a_skewed_reading() {
+    let t = utc(2026, 6, 1, 12, 0, 0);
+    let sources = || {
+        vec![
+            source("a", t),
+            source("b", t + chrono::Duration::milliseconds(100)),
+            source("skewed", t + chrono::Duration::milliseconds(1_000)),
+        ]
+    };
+
+    let mean = ConsensusPolicy { selection: Selection::Mean, ..ConsensusPolicy::default() };
+    let result = consensus(sources()).get_date_time_with(&mean);
+    assert!(close(result.time, t + chrono::Duration::milliseconds(366)), "{}", result.time);
+
+    let median = ConsensusPolicy { selection: Selection::Median, ..ConsensusPolicy::default() };
+    let result = consensus(sources()).get_date_time_with(&median);
+    assert!((result.time - (t + chrono::Duration::milliseconds(100))).abs() < chrono::Duration::milliseconds(50));
+}
+
+#[test]
+fn the_configured_policy_is_the_default_for_get_date_time() {
+    let t = utc(2026, 6, 1, 12, 0, 0);
+    let policy = ConsensusPolicy { min_quorum: Some(4), ..ConsensusPolicy::default() };
+    let result = five_with_two_liars(t).with_policy(policy).get_date_time();
+    assert_eq!(result.path, AgreementPath::Fallback);
+}
+
+#[test]
+fn sources_are_queried_concurrently() {
+    let started = Instant::now();
+    let result = consensus(vec![slow("a", 300), slow("b", 300), slow("c", 300)]).get_date_time();
+
+    assert_eq!(result.path, AgreementPath::Unanimous);
+    assert!(started.elapsed() < Duration::from_millis(600), "took {:?}", started.elapsed());
+}
+
+#[test]
+fn a_hung_source_is_left_behind_at_the_deadline() {
+    let policy = ConsensusPolicy { deadline_ms: 300, ..ConsensusPolicy::default() };
+    let started = Instant::now();
+    let result = consensus(vec![slow("a", 0), slow("hung", 10_000), slow("c", 50)]).get_date_time_with(&policy);
+
+    assert!(started.elapsed() < Duration::from_secs(1), "took {:?}", started.elapsed());
+    assert_eq!(result.path, AgreementPath::Quorum);
+    assert_eq!(result.sources, ["a", "c"]);
+    assert_eq!(result.errors, [("hung".to_string(), SourceError::MissedDeadline)]);
+}
+
+#[test]
+fn missing_sources_still_count_towards_the_quorum() {
+    let policy = ConsensusPolicy { deadline_ms: 200, ..ConsensusPolicy::default() };
+    let result = consensus(vec![slow("a", 0), slow("hung-1", 10_000), slow("hung-2", 10_000)])
+        .get_date_time_with(&policy);
+
+    assert_eq!(result.path, AgreementPath::Fallback);
+}
+
+#[test]
+fn failures_are_reported_per_source_even_on_fallback() {
+    let result = consensus(vec![down("a"), source("b", utc(2024, 1, 1, 0, 0, 0)), down("c")]).get_date_time();
+
+    assert!(result.used_fallback);
+    assert_eq!(
+        result.errors,
+        [("a".to_string(), SourceError::Timeout), ("c".to_string(), SourceError::Timeout)]
+    );
+}
+
+fn down(name: &str) -> Box<dyn TimeSource> {
+    Fake::offset(name, chrono::Duration::zero()).down().boxed()
+}
+
+/// Like [`consensus`], with the software clock already set to `t`.
+fn consensus_at(t: DateTime<Utc>, sources: Vec<Box<dyn TimeSource>>) -> TimeConsensus {
+    TimeConsensus::with_clock(sources, ClockHandle::anchored(Instant::now(), t, LeapSecondTable::default()))
+        .with_not_before(utc(2000, 1, 1, 0, 0, 0))
+}
+
+#[test]
+fn one_source_down_leaves_a_quorum_of_two() {
+    let t = utc(2026, 6, 1, 12, 0, 0);
+    let result = consensus(vec![source("a", t), down("b"), source("c", t)]).get_date_time();
+
+    assert_eq!(result.path, AgreementPath::Quorum);
+    assert_eq!(result.sources, ["a", "c"]);
+    assert!(!result.path.is_degraded());
+}
+
+#[test]
+fn a_majority_of_responders_is_accepted_when_the_quorum_is_unreachable() {
+    let t = utc(2026, 6, 1, 12, 0, 0);
+    let result = consensus(vec![source("a", t), down("b"), source("c", t), down("d"), down("e")]).get_date_time();
+
+    assert_eq!(result.path, AgreementPath::Majority);
+    assert!(result.path.is_degraded());
+    assert!(!result.used_fallback);
+    assert!(close(result.time, t));
+}
+
+#[test]
+fn a_majority_of_responders_outvotes_a_falseticker() {
+    let t = utc(2026, 6, 1, 12, 0, 0);
+    let result = consensus(vec![
+        source("a", t),
+        source("b", t + chrono::Duration::minutes(7)),
+        source("c", t),
+        down("d"),
+        down("e"),
+    ])
+    .get_date_time();
+
+    assert_eq!(result.path, AgreementPath::Majority);
+    assert_eq!(result.sources, ["a", "c"]);
+}
+
+#[test]
+fn two_disagreeing_responders_are_no_majority() {
+    let t = utc(2026, 6, 1, 12, 0, 0);
+    let result = consensus(vec![source("a", t), source("b", t + chrono::Duration::minutes(7)), down("c"), down("d")])
+        .get_date_time();
+
+    assert_eq!(result.path, AgreementPath::Fallback);
+}
+
+#[test]
+fn a_lone_responder_is_accepted_if_the_software_clock_agrees() {
+    let t = utc(2026, 6, 1, 12, 0, 0);
+    let result = consensus_at(t, vec![down("a"), source("b", t + chrono::Duration::milliseconds(300)), down("c")])
+        .get_date_time();
+
+    assert_eq!(result.path, AgreementPath::SingleSource);
+    assert_eq!(result.sources, ["b"]);
+    assert!(close(result.time, t + chrono::Duration::milliseconds(300)));
+}
+
+#[test]
+fn a_lone_responder_is_rejected_if_the_software_clock_disagrees() {
+    let t = utc(2026, 6, 1, 12, 0, 0);
+    let result = consensus_at(t, vec![down("a"), source("b", t + chrono::Duration::minutes(2)), down("c")])
+        .get_date_time();
+
+    assert_eq!(result.path, AgreementPath::Fallback);
+    assert!(close(result.time, t));
+}
+
+#[test]
+fn a_fixed_quorum_disables_the_degraded_tiers() {
+    let t = utc(2026, 6, 1, 12, 0, 0);
+    let policy = ConsensusPolicy { min_quorum: Some(3), ..ConsensusPolicy::default() };
+
+    let majority = consensus(vec![source("a", t), source("b", t), down("c"), down("d"), down("e")]);
+    assert_eq!(majority.get_date_time_with(&policy).path, AgreementPath::Fallback);
+
+    let single = consensus_at(t, vec![source("a", t), down("b"), down("c")]);
+    assert_eq!(single.get_date_time_with(&policy).path, AgreementPath::Fallback);
+}
+
+/// Like [`consensus_at`], but believing nothing before the build.
+fn bounded_at(t: DateTime<Utc>, sources: Vec<Box<dyn TimeSource>>) -> TimeConsensus {
+    TimeConsensus::with_clock(sources, ClockHandle::anchored(Instant::now(), t, LeapSecondTable::default()))
+}
+
+#[test]
+fn the_build_time_is_embedded() {
+    assert!(build_time() > utc(2024, 1, 1, 0, 0, 0));
+    assert!(build_time() <= Utc::now());
+}
+
+#[test]
+fn times_before_the_build_or_past_the_horizon_are_rejected() {
+    let now = Utc::now();
+    let (ancient, distant) = (utc(1999, 12, 31, 0, 0, 0), utc(2150, 1, 1, 0, 0, 0));
+    let policy = ConsensusPolicy::default();
+    let result = bounded_at(now, vec![source("a", now), source("b", ancient), source("c", now), source("d", distant)])
+        .get_date_time_with(&policy);
+
+    assert_eq!(result.path, AgreementPath::Majority);
+    assert_eq!(result.sources, ["a", "c"]);
+    let (not_before, not_after) = (build_time(), policy.not_after());
+    assert_eq!(
+        result.errors,
+        [
+            ("b".to_string(), SourceError::OutOfRange { time: ancient, not_before, not_after }),
+            ("d".to_string(), SourceError::OutOfRange { time: distant, not_before, not_after }),
+        ]
+    );
+}
+
+#[test]
+fn the_software_clock_is_stepped_into_bounds() {
+    let behind = bounded_at(utc(2000, 1, 1, 0, 0, 0), vec![down("a")]);
+    let result = behind.get_date_time();
+    assert!(result.used_fallback);
+    assert!(result.time >= build_time());
+    assert!(behind.fallback_clock().now() >= build_time());
+
+    let policy = ConsensusPolicy { horizon_days: 1, ..ConsensusPolicy::default() };
+    let ahead = bounded_at(utc(2150, 1, 1, 0, 0, 0), vec![down("a")]);
+    let result = ahead.get_date_time_with(&policy);
+    assert!(result.used_fallback);
+    assert!(result.time <= policy.not_after());
+}
+
+#[test]
+fn zero_weight_readings_are_counted_equally() {
+    let t = utc(2026, 6, 1, 12, 0, 0);
+    for selection in [Selection::Mean, Selection::Median] {
+        let policy = ConsensusPolicy { selection, ..ConsensusPolicy::default() };
+        let result = consensus(vec![Fake::at("weightless", t).with_weight(0.0).boxed()]).get_date_time_with(&policy);
+        assert_eq!(result.path, AgreementPath::Unanimous);
+        assert!(close(result.time, t));
+    }
+}
@@ -0,0 +1,73 @@
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, TimeZone, Utc};
+use sybau::{Adjustment, ClockHandle, Discipline, LeapSecondTable};
+
+fn start() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 5, 1, 12, 0, 0).unwrap()
+}
+
+fn ms(n: i64) -> chrono::Duration {
+    chrono::Duration::milliseconds(n)
+}
+
+#[test]
+fn large_offset_steps_immediately() {
+    let origin = Instant::now();
+    let clock = ClockHandle::anchored(origin, start(), LeapSecondTable::default());
+
+    let at = origin + Duration::from_secs(10);
+    let trusted = start() + chrono::Duration::seconds(10) + ms(3_000);
+    assert_eq!(clock.discipline_at(at, trusted), Adjustment::Step(ms(3_000)));
+    assert_eq!(clock.time_at(at), trusted);
+    assert_eq!(clock.time_at(at + Duration::from_secs(60)), trusted + chrono::Duration::seconds(60));
+}
+
+#[test]
+fn small_offset_slews_at_the_configured_rate() {
+    let origin = Instant::now();
+    let clock = ClockHandle::anchored(origin, start(), LeapSecondTable::default())
+        .with_discipline(Discipline { step_threshold: Duration::from_millis(128), max_slew_ppm: 500 });
+
+    let trusted = start() + ms(100);
+    assert_eq!(clock.discipline_at(origin, trusted), Adjustment::Slew(ms(100)));
+    // Nothing jumps at the moment of correction.
+    assert_eq!(clock.time_at(origin), start());
+
+    // 500 ppm of 100 s is 50 ms.
+    let halfway = origin + Duration::from_secs(100);
+    assert_eq!(clock.time_at(halfway), start() + chrono::Duration::seconds(100) + ms(50));
+
+    // After 200 s the whole offset has been absorbed and the rate is nominal again.
+    let done = origin + Duration::from_secs(1_000);
+    assert_eq!(clock.time_at(done), trusted + chrono::Duration::seconds(1_000));
+}
+
+#[test]
+fn negative_offsets_slew_backwards_without_going_back_in_time() {
+    let origin = Instant::now();
+    let clock = ClockHandle::anchored(origin, start(), LeapSecondTable::default());
+
+    assert_eq!(clock.discipline_at(origin, start() - ms(40)), Adjustment::Slew(ms(-40)));
+
+    let mut previous = clock.time_at(origin);
+    for step in 1..=200 {
+        let now = clock.time_at(origin + Duration::from_secs(step));
+        assert!(now > previous);
+        previous = now;
+    }
+    assert_eq!(previous, start() + chrono::Duration::seconds(200) - ms(40));
+}
+
+#[test]
+fn repeated_discipline_replaces_the_pending_slew() {
+    let origin = Instant::now();
+    let clock = ClockHandle::anchored(origin, start(), LeapSecondTable::default());
+
+    clock.discipline_at(origin, start() + ms(100));
+    let later = origin + Duration::from_secs(20);
+    // 10 ms of the first correction has been applied; the new reading asks for 20 ms more.
+    let trusted = start() + chrono::Duration::seconds(20) + ms(30);
+    assert_eq!(clock.discipline_at(later, trusted), Adjustment::Slew(ms(20)));
+    assert_eq!(clock.time_at(later + Duration::from_secs(100)), trusted + chrono::Duration::seconds(100));
+}
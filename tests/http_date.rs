@@ -0,0 +1,41 @@
+use std::time::Duration;
+
+use chrono::{TimeZone, Utc};
+use sybau::{parse_http_date, ParserKind, SourceConfig, SourceError};
+
+mod common;
+
+use common::{http_response, http_server};
+
+#[test]
+fn parses_all_three_http_date_forms() {
+    let expected = Utc.with_ymd_and_hms(1994, 11, 6, 8, 49, 37).unwrap();
+    assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"), Some(expected));
+    assert_eq!(parse_http_date("Sunday, 06-Nov-94 08:49:37 GMT"), Some(expected));
+    assert_eq!(parse_http_date("Sun Nov  6 08:49:37 1994"), Some(expected));
+    assert_eq!(parse_http_date("yesterday-ish"), None);
+}
+
+/// A one-shot loopback HTTP server that answers with the given `Date` header and no body.
+fn server(date: &'static str) -> String {
+    http_server(http_response("404 Not Found", &format!("Date: {date}\r\n"), ""), 1, Duration::ZERO)
+}
+
+#[test]
+fn reads_the_date_header_from_any_server() {
+    let url = server("Tue, 01 Jan 2030 00:00:00 GMT");
+    let sample = SourceConfig::new(url).with_parser(ParserKind::HttpDate).build().fetch().unwrap();
+
+    // Whole-second header: the server's clock was somewhere in [00:00:00, 00:00:01).
+    let stamped = Utc.with_ymd_and_hms(2030, 1, 1, 0, 0, 0).unwrap();
+    assert!(sample.uncertainty >= Duration::from_millis(500));
+    assert!(sample.uncertainty < Duration::from_millis(600));
+    assert!((sample.time() - stamped - chrono::Duration::milliseconds(500)).abs() < chrono::Duration::milliseconds(100));
+}
+
+#[test]
+fn responses_without_a_date_header_are_ignored() {
+    let url = http_server(http_response("200 OK", "", ""), 1, Duration::ZERO);
+    let source = SourceConfig::new(url).with_parser(ParserKind::HttpDate).build();
+    assert!(matches!(source.fetch(), Err(SourceError::Schema(_))));
+}
@@ -0,0 +1,59 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use sybau::{marzullo, TimeInterval};
+
+fn at(secs: i64) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap() + Duration::seconds(secs)
+}
+
+fn interval(from: i64, to: i64) -> TimeInterval {
+    TimeInterval::new(at(from), at(to))
+}
+
+#[test]
+fn finds_the_classic_three_source_intersection() {
+    // Marzullo's textbook example: 8–12, 11–13, 10–12 agree on 11–12.
+    let result = marzullo(&[interval(8, 12), interval(11, 13), interval(10, 12)]).unwrap();
+    assert_eq!(result.interval, interval(11, 12));
+    assert_eq!(result.members, vec![0, 1, 2]);
+}
+
+#[test]
+fn excludes_the_falseticker() {
+    let result = marzullo(&[interval(8, 12), interval(11, 13), interval(14, 15)]).unwrap();
+    assert_eq!(result.interval, interval(11, 12));
+    assert_eq!(result.members, vec![0, 1]);
+}
+
+#[test]
+fn picks_the_largest_consistent_subset() {
+    let result = marzullo(&[
+        interval(0, 10),
+        interval(100, 110),
+        interval(5, 20),
+        interval(105, 120),
+        interval(108, 109),
+    ])
+    .unwrap();
+    assert_eq!(result.interval, interval(108, 109));
+    assert_eq!(result.members, vec![1, 3, 4]);
+}
+
+#[test]
+fn touching_intervals_agree_on_a_single_instant() {
+    let result = marzullo(&[interval(0, 10), interval(10, 20)]).unwrap();
+    assert_eq!(result.interval, interval(10, 10));
+    assert_eq!(result.members.len(), 2);
+}
+
+#[test]
+fn a_lone_interval_is_its_own_intersection() {
+    let result = marzullo(&[interval(3, 7)]).unwrap();
+    assert_eq!(result.interval, interval(3, 7));
+    assert_eq!(result.members, vec![0]);
+    assert_eq!(result.interval.midpoint(), at(5));
+}
+
+#[test]
+fn nothing_to_intersect() {
+    assert_eq!(marzullo(&[]), None);
+}
@@ -0,0 +1,199 @@
+use serde_json::json;
+use std::time::Duration;
+
+use sybau::{JsonPointer, ParserKind, SourceConfig, SourceError, TimeApiIo, TimeSample, WorldClockApi, WorldTimeApi};
+
+mod common;
+
+use common::{http_response, http_server, utc};
+
+fn pointer(pointer: &str, format: Option<&str>) -> JsonPointer {
+    let mut source = SourceConfig::new("https://example.test").with_parser(ParserKind::JsonPointer);
+    source.pointer = Some(pointer.to_string());
+    source.format = format.map(String::from);
+    JsonPointer::new(source)
+}
+
+#[test]
+fn world_time_api_reads_the_offset_timestamp() {
+    let source = WorldTimeApi::new(SourceConfig::new("https://example.test"));
+    let body = json!({"datetime": "2024-05-01T13:45:12.250000+01:00", "timezone": "Europe/London"});
+    assert_eq!(
+        source.parse(&body),
+        Ok(utc(2024, 5, 1, 12, 45, 12) + chrono::Duration::milliseconds(250))
+    );
+    assert!(matches!(source.parse(&json!({"dateTime": "2024-05-01T13:45:12"})), Err(SourceError::Schema(_))));
+    assert!(matches!(source.parse(&json!({"datetime": "yesterday"})), Err(SourceError::Parse(_))));
+}
+
+#[test]
+fn world_time_api_corrects_offsets_the_tz_database_disagrees_with() {
+    let source = WorldTimeApi::new(SourceConfig::new("https://example.test"));
+
+    // Stale tz data still on GMT after the clocks went forward.
+    let stale = json!({"datetime": "2024-04-02T13:45:12+00:00", "timezone": "Europe/London"});
+    let (time, mismatch) = source.parse_checked(&stale).unwrap();
+    assert_eq!(time, utc(2024, 4, 2, 12, 45, 12));
+    let mismatch = mismatch.unwrap();
+    assert_eq!(mismatch.zone, chrono_tz::Europe::London);
+    assert_eq!(mismatch.reported.local_minus_utc(), 0);
+    assert_eq!(mismatch.expected.local_minus_utc(), 3600);
+    assert_eq!(source.parse(&stale), Ok(time));
+
+    let fresh = json!({"datetime": "2024-04-02T13:45:12+01:00", "timezone": "Europe/London"});
+    assert_eq!(source.parse_checked(&fresh), Ok((utc(2024, 4, 2, 12, 45, 12), None)));
+}
+
+#[test]
+fn world_time_api_keeps_the_instant_when_the_local_time_was_skipped() {
+    let source = WorldTimeApi::new(SourceConfig::new("https://example.test"));
+    // London skips 01:00 to 02:00 local that day, so the wall time can't be
+    // right; 01:30+00:00 is still 01:30Z.
+    let stale = json!({"datetime": "2024-03-31T01:30:00+00:00", "timezone": "Europe/London"});
+    let (time, mismatch) = source.parse_checked(&stale).unwrap();
+    assert_eq!(time, utc(2024, 3, 31, 1, 30, 0));
+    assert_eq!(mismatch.unwrap().expected.local_minus_utc(), 3600);
+
+    // 02:30 local does exist, in BST.
+    let stale = json!({"datetime": "2024-03-31T02:30:00+00:00", "timezone": "Europe/London"});
+    assert_eq!(source.parse(&stale), Ok(utc(2024, 3, 31, 1, 30, 0)));
+}
+
+#[test]
+fn world_time_api_accepts_either_offset_in_a_repeated_hour() {
+    let source = WorldTimeApi::new(SourceConfig::new("https://example.test"));
+    for (datetime, hour) in [("2024-10-27T01:30:00+01:00", 0), ("2024-10-27T01:30:00+00:00", 1)] {
+        let body = json!({"datetime": datetime, "timezone": "Europe/London"});
+        assert_eq!(source.parse_checked(&body), Ok((utc(2024, 10, 27, hour, 30, 0), None)));
+    }
+
+    // Neither offset: the earlier reading is taken.
+    let body = json!({"datetime": "2024-10-27T01:30:00+05:00", "timezone": "Europe/London"});
+    let (time, mismatch) = source.parse_checked(&body).unwrap();
+    assert_eq!(time, utc(2024, 10, 27, 0, 30, 0));
+    assert_eq!(mismatch.unwrap().expected.local_minus_utc(), 3600);
+}
+
+#[test]
+fn time_api_io_resolves_the_local_zone() {
+    let source = TimeApiIo::new(SourceConfig::new("https://example.test"));
+
+    let summer = json!({"dateTime": "2024-07-01T13:45:12.1234567", "timeZone": "Europe/London"});
+    assert_eq!(
+        source.parse(&summer),
+        Ok(utc(2024, 7, 1, 12, 45, 12) + chrono::Duration::nanoseconds(123_456_700))
+    );
+
+    let winter = json!({"dateTime": "2024-01-15T09:00:00", "timeZone": "Europe/London"});
+    assert_eq!(source.parse(&winter), Ok(utc(2024, 1, 15, 9, 0, 0)));
+
+    let unknown_zone = json!({"dateTime": "2024-01-15T09:00:00", "timeZone": "Mars/Olympus"});
+    assert!(matches!(source.parse(&unknown_zone), Err(SourceError::Parse(_))));
+}
+
+#[test]
+fn world_clock_api_reads_minute_precision() {
+    let source = WorldClockApi::new(SourceConfig::new("https://example.test"));
+    assert_eq!(
+        source.parse(&json!({"currentDateTime": "2024-05-01T13:45Z"})),
+        Ok(utc(2024, 5, 1, 13, 45, 0))
+    );
+    assert_eq!(
+        source.parse(&json!({"currentDateTime": "2024-05-01T14:45+01:00"})),
+        Ok(utc(2024, 5, 1, 13, 45, 0))
+    );
+}
+
+#[test]
+fn json_pointer_defaults_to_rfc3339() {
+    let body = json!({"data": {"now": "2024-05-01T13:45:12Z"}});
+    assert_eq!(pointer("/data/now", None).parse(&body), Ok(utc(2024, 5, 1, 13, 45, 12)));
+    assert!(matches!(pointer("/data/missing", None).parse(&body), Err(SourceError::Schema(_))));
+}
+
+#[test]
+fn json_pointer_reads_unix_seconds() {
+    let expected = utc(2024, 5, 1, 13, 45, 12);
+    let number = json!({"epoch": expected.timestamp()});
+    assert_eq!(pointer("/epoch", None).parse(&number), Ok(expected));
+
+    let string = json!({"epoch": expected.timestamp().to_string()});
+    assert_eq!(pointer("/epoch", Some("%s")).parse(&string), Ok(expected));
+}
+
+#[test]
+fn json_pointer_applies_custom_formats() {
+    let zoned = json!({"t": "01/05/2024 14:45:12 +0100"});
+    assert_eq!(
+        pointer("/t", Some("%d/%m/%Y %H:%M:%S %z")).parse(&zoned),
+        Ok(utc(2024, 5, 1, 13, 45, 12))
+    );
+
+    // No offset in the format means the value is UTC.
+    let naive = json!({"t": "2024-05-01 13:45:12"});
+    assert_eq!(
+        pointer("/t", Some("%Y-%m-%d %H:%M:%S")).parse(&naive),
+        Ok(utc(2024, 5, 1, 13, 45, 12))
+    );
+}
+
+#[test]
+fn json_pointer_values_without_fractions_are_whole_seconds() {
+    let second = Duration::from_secs(1);
+    let body = json!({"int": 1714571112, "frac": 1714571112.25, "rfc": "2024-05-01T13:45:12Z", "micros": "2024-05-01T13:45:12.123456Z"});
+    assert_eq!(pointer("/int", None).resolution(&body), second);
+    assert_eq!(pointer("/int", Some("%s")).resolution(&body), second);
+    assert_eq!(pointer("/frac", None).resolution(&body), Duration::ZERO);
+    assert_eq!(pointer("/rfc", None).resolution(&body), second);
+    assert_eq!(pointer("/micros", None).resolution(&body), Duration::ZERO);
+    assert_eq!(pointer("/rfc", Some("%Y-%m-%dT%H:%M:%SZ")).resolution(&body), second);
+    assert_eq!(pointer("/micros", Some("%Y-%m-%dT%H:%M:%S%.fZ")).resolution(&body), Duration::ZERO);
+}
+
+#[test]
+fn json_pointer_widens_whole_second_readings() {
+    let url = http_server(http_response("200 OK", "", r#"{"now": 1714571112}"#), 1, Duration::ZERO);
+    let mut source = SourceConfig::new(url).with_parser(ParserKind::JsonPointer);
+    source.pointer = Some("/now".to_string());
+    let sample = source.build().fetch().unwrap();
+    // Truncated somewhere in [12, 13): read as 12.5 ± 0.5 s on top of the round trip.
+    assert!(sample.uncertainty >= Duration::from_millis(500));
+    let midpoint = utc(2024, 5, 1, 13, 45, 12) + chrono::Duration::milliseconds(500);
+    assert!((sample.time() - midpoint).abs() < chrono::Duration::milliseconds(100), "{sample:?}");
+}
+
+#[test]
+fn exchange_places_the_server_stamp_at_the_midpoint() {
+    let sent = utc(2024, 5, 1, 12, 0, 0);
+    // The server stamped 12:00:00.400 during a 600 ms round trip that started at 12:00:00.
+    let server = sent + chrono::Duration::milliseconds(400);
+    let sample = TimeSample::from_exchange(sent, Duration::from_millis(600), server);
+
+    assert_eq!(sample.local, sent + chrono::Duration::milliseconds(600));
+    assert_eq!(sample.offset, chrono::Duration::milliseconds(100));
+    assert_eq!(sample.uncertainty, Duration::from_millis(300));
+    // As of the moment the response arrived, the server's clock read 12:00:00.700.
+    assert_eq!(sample.time(), sent + chrono::Duration::milliseconds(700));
+}
+
+#[test]
+fn slow_exchanges_are_less_certain_but_not_stale() {
+    let sent = utc(2024, 5, 1, 12, 0, 0);
+    let fast = TimeSample::from_exchange(sent, Duration::from_millis(20), sent + chrono::Duration::milliseconds(10));
+    let slow = TimeSample::from_exchange(sent, Duration::from_secs(4), sent + chrono::Duration::seconds(2));
+
+    assert_eq!(fast.offset, chrono::Duration::zero());
+    assert_eq!(slow.offset, chrono::Duration::zero());
+    assert!(slow.uncertainty > fast.uncertainty);
+}
+
+#[test]
+fn the_default_time_api_io_source_is_never_ambiguous() {
+    let (url, _) = sybau::DEFAULT_SOURCES.iter().find(|(_, parser)| *parser == ParserKind::TimeApiIo).unwrap();
+    assert!(url.ends_with("timeZone=UTC"), "{url}");
+
+    // The hour that repeats in London parses to one instant in UTC.
+    let source = TimeApiIo::new(SourceConfig::new(*url));
+    let body = json!({"dateTime": "2024-10-27T01:30:00", "timeZone": "UTC"});
+    assert_eq!(source.parse(&body), Ok(utc(2024, 10, 27, 1, 30, 0)));
+}
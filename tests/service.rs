@@ -0,0 +1,114 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::Receiver;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use sybau::{
+    ClockHandle, LeapSecondTable, ResyncPolicy, TimeConsensus, TimeEvent, TimeService, TimeSource,
+};
+
+mod common;
+
+use common::Fake;
+
+/// A service over three sources that share one on/off switch. It only
+/// resyncs when asked to.
+fn service(up: &Arc<AtomicBool>) -> TimeService {
+    let sources: Vec<Box<dyn TimeSource>> = ["a", "b", "c"]
+        .into_iter()
+        .map(|name| {
+            Fake::offset(name, chrono::Duration::hours(1)).with_uncertainty(Duration::from_millis(10)).with_switch(up).boxed()
+        })
+        .collect();
+    let clock = ClockHandle::anchored(Instant::now(), Utc::now(), LeapSecondTable::default());
+    let resync = ResyncPolicy { interval_secs: 3_600, retry_secs: 3_600, jitter_ms: 0, ..ResyncPolicy::default() };
+    TimeService::new(TimeConsensus::with_clock(sources, clock), resync)
+}
+
+fn next(events: &Receiver<TimeEvent>) -> TimeEvent {
+    events.recv_timeout(Duration::from_secs(5)).expect("no event")
+}
+
+#[test]
+fn first_agreement_reports_the_offset() {
+    let up = Arc::new(AtomicBool::new(true));
+    let service = service(&up);
+    let events = service.subscribe();
+    service.start();
+
+    match next(&events) {
+        TimeEvent::OffsetChanged { previous: None, offset } => {
+            assert!((offset - chrono::Duration::hours(1)).abs() < chrono::Duration::milliseconds(100));
+        }
+        other => panic!("unexpected {other:?}"),
+    }
+    assert!((service.now() - (Utc::now() + chrono::Duration::hours(1))).abs() < chrono::Duration::milliseconds(100));
+    assert!(!service.last_sync().unwrap().used_fallback);
+}
+
+#[test]
+fn reports_falling_back_and_recovering() {
+    let up = Arc::new(AtomicBool::new(true));
+    let service = service(&up);
+    let events = service.subscribe();
+    service.start();
+    assert!(matches!(next(&events), TimeEvent::OffsetChanged { .. }));
+
+    up.store(false, Ordering::SeqCst);
+    service.resync_now();
+    assert!(matches!(next(&events), TimeEvent::FellBack(result) if result.used_fallback));
+
+    // Still down: no second report.
+    service.resync_now();
+    assert!(events.recv_timeout(Duration::from_millis(300)).is_err());
+
+    up.store(true, Ordering::SeqCst);
+    service.resync_now();
+    assert!(matches!(next(&events), TimeEvent::Recovered(result) if !result.used_fallback));
+    // The offset is where it was before the outage.
+    assert!(events.recv_timeout(Duration::from_millis(300)).is_err());
+}
+
+#[test]
+fn callbacks_receive_events() {
+    let up = Arc::new(AtomicBool::new(false));
+    let service = service(&up);
+    let seen = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&seen);
+    service.on_event(move |event| {
+        if matches!(event, TimeEvent::FellBack(_)) {
+            counter.fetch_add(1, Ordering::SeqCst);
+        }
+    });
+    let events = service.subscribe();
+    service.start();
+
+    next(&events);
+    assert_eq!(seen.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn stops_promptly_mid_wait() {
+    let up = Arc::new(AtomicBool::new(true));
+    let service = service(&up);
+    let events = service.subscribe();
+    service.start();
+    next(&events);
+
+    let started = Instant::now();
+    drop(service);
+    assert!(started.elapsed() < Duration::from_secs(1));
+}
+
+#[test]
+fn backs_off_exponentially_up_to_the_cap() {
+    let resync = ResyncPolicy { interval_secs: 600, retry_secs: 5, max_backoff_secs: 60, ..ResyncPolicy::default() };
+
+    assert_eq!(resync.backoff(0), Duration::from_secs(600));
+    assert_eq!(resync.backoff(1), Duration::from_secs(5));
+    assert_eq!(resync.backoff(2), Duration::from_secs(10));
+    assert_eq!(resync.backoff(4), Duration::from_secs(40));
+    assert_eq!(resync.backoff(5), Duration::from_secs(60));
+    assert_eq!(resync.backoff(200), Duration::from_secs(60));
+}
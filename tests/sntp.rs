@@ -0,0 +1,115 @@
+use std::net::UdpSocket;
+use std::time::Duration;
+
+use chrono::{TimeZone, Utc};
+use sybau::{from_ntp, to_ntp, ParserKind, SourceConfig, SourceError, Sntp};
+
+mod common;
+
+use common::sntp_responder;
+
+fn client(url: String) -> Sntp {
+    let mut source = SourceConfig::new(url).with_parser(ParserKind::Sntp);
+    source.timeout_ms = 1_000;
+    Sntp::new(source)
+}
+
+#[test]
+fn computes_offset_from_the_four_timestamps() {
+    let skew = chrono::Duration::seconds(5);
+    let sample = client(sntp_responder(skew, Duration::ZERO, |_| {})).query().unwrap();
+
+    assert!((sample.offset - skew).abs() < chrono::Duration::milliseconds(50), "{sample:?}");
+    assert!(sample.delay >= chrono::Duration::zero());
+    assert!(sample.delay < chrono::Duration::milliseconds(50), "{sample:?}");
+    assert!((sample.time - (Utc::now() + skew)).abs() < chrono::Duration::milliseconds(100));
+}
+
+#[test]
+fn server_processing_time_is_not_counted_as_delay() {
+    let hold = Duration::from_millis(200);
+    let sample = client(sntp_responder(chrono::Duration::zero(), hold, |_| {})).query().unwrap();
+
+    assert!(sample.offset.abs() < chrono::Duration::milliseconds(50), "{sample:?}");
+    assert!(sample.delay < chrono::Duration::milliseconds(50), "{sample:?}");
+}
+
+#[test]
+fn plugs_in_as_a_time_source() {
+    let source = SourceConfig::new(sntp_responder(chrono::Duration::hours(1), Duration::ZERO, |_| {}))
+        .with_parser(ParserKind::Sntp)
+        .build();
+    let sample = source.fetch().unwrap();
+    assert!(sample.uncertainty < Duration::from_millis(25));
+    let time = sample.time();
+    assert!((time - (Utc::now() + chrono::Duration::hours(1))).abs() < chrono::Duration::milliseconds(100));
+}
+
+#[test]
+fn rejects_kiss_of_death() {
+    let url = sntp_responder(chrono::Duration::zero(), Duration::ZERO, |reply| reply[1] = 0);
+    assert_eq!(client(url).query(), Err(SourceError::Protocol("kiss-o'-death".to_string())));
+}
+
+#[test]
+fn rejects_broadcast_replies() {
+    let url = sntp_responder(chrono::Duration::zero(), Duration::ZERO, |reply| reply[0] = (4 << 3) | 5);
+    assert_eq!(client(url).query(), Err(SourceError::Protocol("reply is not from a server".to_string())));
+}
+
+#[test]
+fn rejects_unsynchronized_servers() {
+    let url = sntp_responder(chrono::Duration::zero(), Duration::ZERO, |reply| reply[0] |= 0b1100_0000);
+    assert!(matches!(client(url).query(), Err(SourceError::Protocol(_))));
+}
+
+#[test]
+fn rejects_replies_sent_before_the_request_arrived() {
+    let swapped = |reply: &mut [u8; 48]| {
+        let t2: [u8; 8] = reply[32..40].try_into().unwrap();
+        reply.copy_within(40..48, 32);
+        reply[40..48].copy_from_slice(&t2);
+    };
+    let url = sntp_responder(chrono::Duration::zero(), Duration::from_millis(50), swapped);
+    assert_eq!(
+        client(url).query(),
+        Err(SourceError::Protocol("reply was sent before the request arrived".to_string()))
+    );
+}
+
+#[test]
+fn rejects_replies_held_longer_than_the_round_trip() {
+    // T2 a day before T3: more time at the server than the whole exchange took.
+    let url = sntp_responder(chrono::Duration::zero(), Duration::ZERO, |reply| {
+        let t2 = from_ntp(&reply[32..40]).unwrap() - chrono::Duration::days(1);
+        reply[32..40].copy_from_slice(&to_ntp(t2));
+    });
+    assert_eq!(client(url).query(), Err(SourceError::Protocol("negative round-trip delay".to_string())));
+}
+
+#[test]
+fn rejects_replies_to_someone_else() {
+    let url = sntp_responder(chrono::Duration::zero(), Duration::ZERO, |reply| reply[24] ^= 0xff);
+    assert!(matches!(client(url).query(), Err(SourceError::Protocol(_))));
+}
+
+#[test]
+fn times_out_when_nobody_answers() {
+    let silent = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let mut source = SourceConfig::new(silent.local_addr().unwrap().to_string()).with_parser(ParserKind::Sntp);
+    source.timeout_ms = 100;
+    assert_eq!(Sntp::new(source).query(), Err(SourceError::Timeout));
+}
+
+#[test]
+fn ntp_timestamps_round_trip_across_the_2036_era_rollover() {
+    for time in [
+        Utc.with_ymd_and_hms(2026, 10, 18, 12, 0, 0).unwrap() + chrono::Duration::microseconds(500_001),
+        Utc.with_ymd_and_hms(2036, 2, 7, 6, 28, 15).unwrap(),
+        Utc.with_ymd_and_hms(2036, 2, 7, 6, 28, 17).unwrap(),
+        Utc.with_ymd_and_hms(2050, 1, 1, 0, 0, 0).unwrap(),
+    ] {
+        let decoded = from_ntp(&to_ntp(time)).unwrap();
+        assert!((decoded - time).abs() < chrono::Duration::nanoseconds(2), "{time} -> {decoded}");
+    }
+}
@@ -0,0 +1,143 @@
+use std::time::Instant;
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use proptest::prelude::*;
+use sybau::{ClockHandle, LeapSecondTable, SoftClock};
+
+mod common;
+
+use common::utc;
+
+fn ticked(start: DateTime<Utc>, ticks: u32, leap_seconds: &LeapSecondTable) -> SoftClock {
+    let mut clock = SoftClock::from_utc(start);
+    for _ in 0..ticks {
+        clock.tick_with(leap_seconds);
+    }
+    clock
+}
+
+/// Starting points a second or a few away from month, year and century rollovers.
+fn near_boundary() -> impl Strategy<Value = DateTime<Utc>> {
+    (1901i32..2399, 1u32..=12, 0i64..7200).prop_map(|(year, month, back)| {
+        let next_month = if month == 12 {
+            utc(year + 1, 1, 1, 0, 0, 0)
+        } else {
+            utc(year, month + 1, 1, 0, 0, 0)
+        };
+        next_month - Duration::seconds(back)
+    })
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(2000))]
+
+    #[test]
+    fn tick_matches_chrono_anywhere(secs in 0i64..13_000_000_000, ticks in 0u32..20_000) {
+        let start = DateTime::from_timestamp(secs, 0).unwrap();
+        let clock = ticked(start, ticks, &LeapSecondTable::default());
+        prop_assert_eq!(clock.to_utc(), Some(start + Duration::seconds(ticks as i64)));
+    }
+
+    #[test]
+    fn tick_matches_chrono_across_boundaries(start in near_boundary(), ticks in 0u32..20_000) {
+        let clock = ticked(start, ticks, &LeapSecondTable::default());
+        prop_assert_eq!(clock.to_utc(), Some(start + Duration::seconds(ticks as i64)));
+    }
+
+    #[test]
+    fn anchored_clock_agrees_with_ticking(start in near_boundary(), ticks in 0u32..20_000) {
+        let table = LeapSecondTable::iers();
+        let origin = Instant::now();
+        let clock = ClockHandle::anchored(origin, start, table.clone());
+        let at = origin + std::time::Duration::from_secs(ticks as u64);
+        prop_assert_eq!(SoftClock::from_utc(clock.time_at(at)), ticked(start, ticks, &table));
+    }
+
+    #[test]
+    fn tick_never_produces_an_impossible_date(start in near_boundary(), ticks in 0u32..20_000) {
+        let clock = ticked(start, ticks, &LeapSecondTable::default());
+        prop_assert!(clock.to_utc().is_some(), "invalid reading {:?}", clock);
+    }
+}
+
+#[test]
+fn february_has_28_or_29_days() {
+    let common = ticked(utc(2023, 2, 28, 23, 59, 59), 1, &LeapSecondTable::default());
+    assert_eq!((common.day, common.month), (1, 3));
+
+    let leap = ticked(utc(2024, 2, 28, 23, 59, 59), 1, &LeapSecondTable::default());
+    assert_eq!((leap.day, leap.month), (29, 2));
+
+    let century = ticked(utc(1900, 2, 28, 23, 59, 59), 1, &LeapSecondTable::default());
+    assert_eq!((century.day, century.month), (1, 3));
+
+    let quad_century = ticked(utc(2000, 2, 28, 23, 59, 59), 1, &LeapSecondTable::default());
+    assert_eq!((quad_century.day, quad_century.month), (29, 2));
+}
+
+#[test]
+fn thirty_day_months_roll_over_after_the_30th() {
+    for month in [4, 6, 9, 11] {
+        let clock = ticked(utc(2025, month, 30, 23, 59, 59), 1, &LeapSecondTable::default());
+        assert_eq!((clock.day, clock.month), (1, month as u8 + 1));
+    }
+}
+
+#[test]
+fn leap_second_inserts_23_59_60() {
+    let table = LeapSecondTable::iers();
+    let start = utc(2016, 12, 31, 23, 59, 59);
+
+    let leap = ticked(start, 1, &table);
+    assert_eq!((leap.hour, leap.min, leap.sec), (23, 59, 60));
+    assert_eq!(leap.to_utc().unwrap().timestamp(), start.timestamp());
+
+    let after = ticked(start, 2, &table);
+    assert_eq!(after.to_utc(), Some(utc(2017, 1, 1, 0, 0, 0)));
+}
+
+#[test]
+fn leap_second_table_only_affects_listed_days() {
+    let table = LeapSecondTable::new(vec![NaiveDate::from_ymd_opt(2030, 6, 30).unwrap()]);
+
+    let listed = ticked(utc(2030, 6, 30, 23, 59, 59), 1, &table);
+    assert_eq!(listed.sec, 60);
+
+    let unlisted = ticked(utc(2030, 12, 31, 23, 59, 59), 1, &table);
+    assert_eq!(unlisted.to_utc(), Some(utc(2031, 1, 1, 0, 0, 0)));
+}
+
+#[test]
+fn elapsed_ticks_include_leap_seconds() {
+    let table = LeapSecondTable::iers();
+    let start = utc(2015, 6, 30, 12, 0, 0);
+    // One extra tick is spent on 23:59:60.
+    let clock = ticked(start, 86_401, &table);
+    assert_eq!(clock.to_utc(), Some(start + Duration::days(1)));
+}
+
+#[test]
+fn anchored_clock_keeps_sub_second_precision_over_days() {
+    let origin = Instant::now();
+    let start = utc(2026, 3, 1, 0, 0, 0) + Duration::milliseconds(250);
+    let clock = ClockHandle::anchored(origin, start, LeapSecondTable::default());
+
+    let elapsed = std::time::Duration::from_secs(5 * 86_400) + std::time::Duration::from_millis(900);
+    assert_eq!(
+        clock.time_at(origin + elapsed),
+        utc(2026, 3, 6, 0, 0, 1) + Duration::milliseconds(150)
+    );
+}
+
+#[test]
+fn anchored_clock_shows_the_leap_second() {
+    let origin = Instant::now();
+    let start = utc(2016, 12, 31, 23, 59, 59) + Duration::milliseconds(500);
+    let clock = ClockHandle::anchored(origin, start, LeapSecondTable::iers());
+
+    let during = SoftClock::from_utc(clock.time_at(origin + std::time::Duration::from_secs(1)));
+    assert_eq!((during.hour, during.min, during.sec), (23, 59, 60));
+
+    let after = clock.time_at(origin + std::time::Duration::from_secs(2));
+    assert_eq!(after, utc(2017, 1, 1, 0, 0, 0) + Duration::milliseconds(500));
+}
@@ -0,0 +1,73 @@
+use std::net::TcpListener;
+use std::time::{Duration, Instant};
+
+use chrono::DateTime;
+use sybau::{fetch_time_from_url, ClockHandle, LeapSecondTable, ParserKind, SourceConfig, SourceError, TimeConsensus};
+
+mod common;
+
+use common::{http_response, http_server};
+
+#[test]
+fn error_statuses_are_reported_as_such() {
+    let url = http_server(http_response("503 Service Unavailable", "", "{}"), 1, Duration::ZERO);
+    assert_eq!(fetch_time_from_url(&url), Err(SourceError::HttpStatus(503)));
+}
+
+#[test]
+fn non_json_bodies_are_unreadable() {
+    let url = http_server(http_response("200 OK", "", "<html>maintenance</html>"), 1, Duration::ZERO);
+    assert!(matches!(fetch_time_from_url(&url), Err(SourceError::Body(_))));
+}
+
+#[test]
+fn json_of_the_wrong_shape_is_a_schema_error() {
+    let url = http_server(http_response("200 OK", "", r#"{"unixtime": 1714571112}"#), 1, Duration::ZERO);
+    assert!(matches!(fetch_time_from_url(&url), Err(SourceError::Schema(_))));
+}
+
+#[test]
+fn bad_timestamps_are_parse_errors() {
+    let url = http_server(http_response("200 OK", "", r#"{"datetime": "soon"}"#), 1, Duration::ZERO);
+    assert!(matches!(fetch_time_from_url(&url), Err(SourceError::Parse(_))));
+}
+
+#[test]
+fn refused_connections_are_connect_errors() {
+    let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    assert!(matches!(fetch_time_from_url(&format!("http://127.0.0.1:{port}/")), Err(SourceError::Connect(_))));
+}
+
+#[test]
+fn slow_servers_time_out() {
+    let url = http_server(http_response("200 OK", "", "{}"), 1, Duration::from_secs(2));
+    let mut source = SourceConfig::new(url).with_parser(ParserKind::WorldTimeApi);
+    source.timeout_ms = 200;
+    assert_eq!(source.build().fetch(), Err(SourceError::Timeout));
+}
+
+#[test]
+fn corrected_offsets_keep_the_reading() {
+    let url = http_server(
+        http_response("200 OK", "", r#"{"datetime": "2024-04-02T13:45:12+00:00", "timezone": "Europe/London"}"#),
+        1,
+        Duration::ZERO,
+    );
+    let sample = SourceConfig::new(url).with_parser(ParserKind::WorldTimeApi).build().fetch().unwrap();
+    let mismatch = sample.offset_mismatch.unwrap();
+    assert_eq!(mismatch.expected.local_minus_utc(), 3600);
+    assert!(SourceError::OffsetMismatch(mismatch).to_string().contains("Europe/London"));
+}
+
+#[test]
+fn corrected_offsets_are_warnings_not_failures() {
+    let body = r#"{"datetime": "2024-04-02T13:45:12+00:00", "timezone": "Europe/London"}"#;
+    let url = http_server(http_response("200 OK", "", body), 1, Duration::ZERO);
+    let source = SourceConfig::new(url.clone()).with_parser(ParserKind::WorldTimeApi).build();
+    let clock = ClockHandle::anchored(Instant::now(), DateTime::UNIX_EPOCH, LeapSecondTable::default());
+    let result = TimeConsensus::with_clock(vec![source], clock).with_not_before(DateTime::UNIX_EPOCH).get_date_time();
+
+    assert_eq!(result.readings.len(), 1);
+    assert!(result.errors.is_empty());
+    assert!(matches!(&result.warnings[..], [(name, SourceError::OffsetMismatch(_))] if *name == url));
+}
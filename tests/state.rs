@@ -0,0 +1,138 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+use std::time::Instant;
+
+use chrono::DateTime;
+use sybau::{
+    AgreementPath, ClockHandle, Config, LeapSecondTable, SourceError, StateFile, TimeConsensus, TimeSource,
+};
+
+mod common;
+
+use common::{sources, utc};
+
+/// A fallback clock that thinks it's 1970, as after booting with a dead RTC.
+fn dead_rtc() -> ClockHandle {
+    ClockHandle::anchored(Instant::now(), DateTime::UNIX_EPOCH, LeapSecondTable::default())
+}
+
+/// Fixtures predate the build, so only the floor bounds them here.
+fn consensus(sources: Vec<Box<dyn TimeSource>>, path: &Path) -> TimeConsensus {
+    TimeConsensus::with_clock(sources, dead_rtc()).with_not_before(DateTime::UNIX_EPOCH).with_state_file(path)
+}
+
+/// A fresh path in the temp directory, removed first in case of a previous run.
+fn state_path(test: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("sybau-{}-{test}", std::process::id()));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn stored_times_load_back() {
+    let path = state_path("round-trip");
+    let state = StateFile::new(&path);
+    assert_eq!(state.load().unwrap(), None);
+
+    let time = utc(2024, 5, 1, 12, 0, 0) + chrono::Duration::microseconds(250);
+    state.store(time).unwrap();
+    assert_eq!(state.load().unwrap(), Some(time));
+    state.store(time + chrono::Duration::hours(1)).unwrap();
+    assert_eq!(state.load().unwrap(), Some(time + chrono::Duration::hours(1)));
+
+    let mut temporary = path.clone().into_os_string();
+    temporary.push(".tmp");
+    assert!(!PathBuf::from(temporary).exists());
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn corrupt_state_files_are_invalid_data() {
+    let path = state_path("corrupt");
+    fs::write(&path, "not a time\n").unwrap();
+    assert_eq!(StateFile::new(&path).load().unwrap_err().kind(), io::ErrorKind::InvalidData);
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn agreed_times_are_persisted() {
+    let path = state_path("persisted");
+    let time = utc(2024, 5, 1, 12, 0, 0);
+    let consensus = consensus(sources(&[("a", time), ("b", time)]), &path);
+
+    let result = consensus.get_date_time();
+    assert!(!result.used_fallback);
+    assert_eq!(StateFile::new(&path).load().unwrap(), Some(result.time));
+    assert_eq!(consensus.floor(), Some(result.time));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn concurrent_runs_leave_the_latest_time_on_disk() {
+    let path = state_path("concurrent");
+    let time = utc(2024, 5, 1, 12, 0, 0);
+    let consensus = Arc::new(consensus(sources(&[("a", time), ("b", time)]), &path));
+
+    let runs: Vec<_> = (0..8)
+        .map(|_| {
+            let consensus = Arc::clone(&consensus);
+            thread::spawn(move || {
+                for _ in 0..5 {
+                    consensus.get_date_time();
+                }
+            })
+        })
+        .collect();
+    for run in runs {
+        run.join().unwrap();
+    }
+
+    assert_eq!(StateFile::new(&path).load().unwrap(), consensus.floor());
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn the_clock_starts_no_earlier_than_the_floor() {
+    let path = state_path("startup");
+    let floor = utc(2024, 5, 1, 12, 0, 0);
+    StateFile::new(&path).store(floor).unwrap();
+
+    let consensus = consensus(Vec::new(), &path);
+    assert_eq!(consensus.floor(), Some(floor));
+    assert!(consensus.fallback_clock().now() >= floor);
+
+    let result = consensus.get_date_time();
+    assert_eq!(result.path, AgreementPath::Fallback);
+    assert!(result.time >= floor);
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn sources_before_the_floor_are_rejected() {
+    let path = state_path("rejected");
+    let floor = utc(2024, 5, 1, 12, 0, 0);
+    StateFile::new(&path).store(floor).unwrap();
+
+    let stale = utc(2001, 1, 1, 0, 0, 0);
+    let consensus = consensus(sources(&[("a", stale), ("b", stale)]), &path);
+    let result = consensus.get_date_time();
+
+    assert!(result.used_fallback);
+    assert!(result.time >= floor);
+    assert!(result.readings.is_empty());
+    assert_eq!(result.errors.len(), 2);
+    assert_eq!(result.errors[0].1, SourceError::BeforeFloor { time: stale, floor });
+    // The rejected times never reach the state file.
+    assert_eq!(StateFile::new(&path).load().unwrap(), Some(floor));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn the_state_file_can_be_configured() {
+    let config = Config::from_toml("state_file = \"/var/lib/sybau/clock\"\n[[sources]]\nurl = \"https://example.test\"").unwrap();
+    assert_eq!(config.state_file, Some(PathBuf::from("/var/lib/sybau/clock")));
+    assert_eq!(Config::default().state_file, None);
+}
@@ -0,0 +1,104 @@
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, TimeZone, Utc};
+use sybau::{ClockHandle, LeapSecondTable, TimeConsensus, TrustOutcome, TrustPolicy, TrustTracker};
+
+mod common;
+
+use common::{Fake, Report};
+
+fn when() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 6, 1, 12, 0, 0).unwrap()
+}
+
+#[test]
+fn unknown_sources_are_fully_trusted() {
+    let tracker = TrustTracker::new(TrustPolicy::default());
+    assert_eq!(tracker.score_at("anyone", Instant::now()), 1.0);
+    assert!(tracker.report_at(Instant::now()).is_empty());
+}
+
+#[test]
+fn disagreement_costs_trust_and_agreement_keeps_it() {
+    let mut tracker = TrustTracker::new(TrustPolicy { penalty: 0.5, half_life_secs: 3_600 });
+    let at = Instant::now();
+
+    tracker.record_at("drifty", TrustOutcome::Disagreed, at, when());
+    tracker.record_at("drifty", TrustOutcome::Disagreed, at, when());
+    tracker.record_at("steady", TrustOutcome::Agreed, at, when());
+
+    assert_eq!(tracker.score_at("drifty", at), 0.25);
+    assert_eq!(tracker.score_at("steady", at), 1.0);
+}
+
+#[test]
+fn lost_trust_recovers_with_the_half_life() {
+    let mut tracker = TrustTracker::new(TrustPolicy { penalty: 0.2, half_life_secs: 600 });
+    let at = Instant::now();
+    tracker.record_at("drifty", TrustOutcome::Disagreed, at, when());
+
+    assert!((tracker.score_at("drifty", at) - 0.2).abs() < 1e-9);
+    // Half of the missing 0.8 returns after one half-life, three quarters after two.
+    assert!((tracker.score_at("drifty", at + Duration::from_secs(600)) - 0.6).abs() < 1e-9);
+    assert!((tracker.score_at("drifty", at + Duration::from_secs(1_200)) - 0.8).abs() < 1e-9);
+}
+
+#[test]
+fn history_is_bounded_and_ordered() {
+    let mut tracker = TrustTracker::new(TrustPolicy::default());
+    let at = Instant::now();
+    for n in 0..(TrustTracker::HISTORY as i64 + 5) {
+        let outcome = if n % 2 == 0 { TrustOutcome::Agreed } else { TrustOutcome::Disagreed };
+        tracker.record_at("a", outcome, at, when() + chrono::Duration::seconds(n));
+    }
+
+    let report = tracker.report_at(at);
+    assert_eq!(report.len(), 1);
+    let history = &report[0].history;
+    assert_eq!(history.len(), TrustTracker::HISTORY);
+    assert_eq!(history[0].at, when() + chrono::Duration::seconds(5));
+    assert!(history.windows(2).all(|w| w[0].at < w[1].at));
+    assert_eq!(history.last().unwrap().score, report[0].score);
+}
+
+fn sources(drift: &Arc<AtomicI64>, drifty_weight: f64) -> TimeConsensus {
+    let fixed = |name| Fake::offset(name, chrono::Duration::zero()).with_uncertainty(Duration::from_millis(50)).boxed();
+    let drifty = Fake::new("drifty", Report::OffsetMs(Arc::clone(drift)))
+        .with_uncertainty(Duration::from_millis(50))
+        .with_weight(drifty_weight)
+        .boxed();
+    let clock = ClockHandle::anchored(Instant::now(), when(), LeapSecondTable::default());
+    TimeConsensus::with_clock(vec![fixed("a"), fixed("b"), drifty], clock)
+}
+
+#[test]
+fn a_drifting_provider_is_demoted_and_visible_in_diagnostics() {
+    let drift = Arc::new(AtomicI64::new(120_000));
+    let consensus = sources(&drift, 1.0);
+
+    for _ in 0..3 {
+        assert!(!consensus.get_date_time().used_fallback);
+    }
+
+    let report = consensus.trust_report();
+    let drifty = report.iter().find(|t| t.name == "drifty").unwrap();
+    assert!(drifty.score < 0.13, "{drifty:?}");
+    assert_eq!(drifty.history.len(), 3);
+    assert!(drifty.history.iter().all(|e| e.outcome == TrustOutcome::Disagreed));
+    assert!(report.iter().filter(|t| t.name != "drifty").all(|t| t.score == 1.0));
+
+    // Back in range, the demoted source barely moves the answer.
+    drift.store(1_000, Ordering::SeqCst);
+    let offset = consensus.get_date_time().time - Utc::now();
+    assert!(offset < chrono::Duration::milliseconds(200), "offset {offset}");
+}
+
+#[test]
+fn configured_weights_still_apply() {
+    let drift = Arc::new(AtomicI64::new(900));
+    let light = sources(&drift, 1.0).get_date_time().time - Utc::now();
+    let heavy = sources(&drift, 8.0).get_date_time().time - Utc::now();
+    assert!(heavy > light + chrono::Duration::milliseconds(300), "light {light}, heavy {heavy}");
+}
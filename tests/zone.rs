@@ -0,0 +1,27 @@
+use chrono::{DateTime, Offset, Utc};
+use chrono_tz::America::New_York;
+use sybau::{ConsensusTime, TimeConsensus};
+
+fn at(rfc3339: &str) -> ConsensusTime {
+    let mut result = TimeConsensus::with_sources(Vec::new()).get_date_time();
+    result.time = DateTime::parse_from_rfc3339(rfc3339).unwrap().with_timezone(&Utc);
+    result
+}
+
+#[test]
+fn in_zone_follows_daylight_saving() {
+    let summer = at("2024-07-01T12:00:00Z").in_zone(New_York);
+    assert_eq!(summer.format("%H:%M %Z").to_string(), "08:00 EDT");
+    assert_eq!(summer.offset().fix().local_minus_utc(), -4 * 3600);
+
+    let winter = at("2024-01-01T12:00:00Z").in_zone(New_York);
+    assert_eq!(winter.format("%H:%M %Z").to_string(), "07:00 EST");
+    assert_eq!(winter.offset().fix().local_minus_utc(), -5 * 3600);
+}
+
+#[test]
+fn in_zone_keeps_the_instant() {
+    let result = at("2024-03-10T07:30:00Z");
+    assert_eq!(result.in_zone(New_York).with_timezone(&Utc), result.time);
+    assert_eq!(result.in_zone(New_York).format("%H:%M %Z").to_string(), "03:30 EDT");
+}